@@ -0,0 +1,179 @@
+use crate::map::Layer; // Import map layers
+
+/// Landscape glyphs that block movement unless a room lists its own
+pub const DEFAULT_SOLID_GLYPHS: &str = "▒♠";
+
+/// Which cells of a room the character can't walk through
+pub struct CollisionMap {
+    width: usize,
+    height: usize,
+    solid: Vec<bool>, // One entry per cell, row by row
+}
+
+/// The cells of a sprite that take part in collisions, relative to its top-left corner
+pub struct Footprint {
+    cells: Vec<(i32, i32)>,
+}
+
+impl CollisionMap {
+    /// Build a collision map from a landscape, treating `solid_glyphs` as walls
+    pub fn from_landscape(landscape: &Layer, solid_glyphs: &str) -> CollisionMap {
+        CollisionMap::from_rows(landscape, |char_in_cell| solid_glyphs.contains(char_in_cell))
+    }
+
+    /// Build a collision map from a dedicated collision layer, where every non-space cell is solid
+    pub fn from_collision_layer(collision_layer: &Layer) -> CollisionMap {
+        CollisionMap::from_rows(collision_layer, |char_in_cell| char_in_cell != ' ')
+    }
+
+    // Function to build a collision map by testing every cell of a layer
+    fn from_rows(layer: &Layer, is_solid: impl Fn(char) -> bool) -> CollisionMap {
+        let mut solid = Vec::with_capacity(layer.width * layer.height);
+        for line in &layer.rows {
+            solid.extend(line.chars().map(&is_solid));
+        }
+        CollisionMap {
+            width: layer.width,
+            height: layer.height,
+            solid,
+        }
+    }
+
+    /// Whether a cell is solid; cells outside the room never are
+    pub fn is_solid(&self, col: i32, row: i32) -> bool {
+        if col < 0 || row < 0 || col as usize >= self.width || row as usize >= self.height {
+            return false;
+        }
+        self.solid[row as usize * self.width + col as usize]
+    }
+
+    /// Whether a footprint placed with its top-left corner at (x, y) overlaps a solid cell
+    pub fn collides(&self, footprint: &Footprint, x: i32, y: i32) -> bool {
+        footprint
+            .cells
+            .iter()
+            .any(|(col, row)| self.is_solid(x + col, y + row))
+    }
+
+    /// Whether moving a footprint from one position to another should be refused.
+    ///
+    /// A move is blocked when it overlaps a solid cell the footprint didn't
+    /// overlap before, so a character that ends up overlapping a wall (e.g.
+    /// after entering a room) can walk out of it again, but not further in.
+    pub fn blocks_move(&self, footprint: &Footprint, from: (i32, i32), to: (i32, i32)) -> bool {
+        let solid_under = |(x, y): (i32, i32)| {
+            footprint
+                .cells
+                .iter()
+                .map(move |(col, row)| (x + col, y + row))
+                .filter(|&(col, row)| self.is_solid(col, row))
+        };
+        let overlapped: Vec<(i32, i32)> = solid_under(from).collect(); // Solid cells the footprint already overlaps
+        solid_under(to).any(|cell| !overlapped.contains(&cell))
+    }
+}
+
+impl Footprint {
+    /// Every cell of the sprite's width × height rectangle is part of its
+    /// footprint, blank or not
+    pub fn from_sprite(sprite: &[String]) -> Footprint {
+        let width = sprite.iter().map(|line| line.chars().count()).max().unwrap_or(0); // Widest row of the sprite
+        let mut cells = Vec::new();
+        for row in 0..sprite.len() {
+            for col in 0..width {
+                cells.push((col as i32, row as i32));
+            }
+        }
+        Footprint { cells }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sdl2::pixels::Color;
+
+    fn layer(rows: &[&str]) -> Layer {
+        Layer {
+            color: Color::GREEN,
+            width: rows[0].chars().count(),
+            height: rows.len(),
+            rows: rows.iter().map(|row| row.to_string()).collect(),
+        }
+    }
+
+    fn sprite(rows: &[&str]) -> Vec<String> {
+        rows.iter().map(|row| row.to_string()).collect()
+    }
+
+    #[test]
+    fn solid_glyphs_come_from_the_landscape() {
+        let collision = CollisionMap::from_landscape(&layer(&["  ▒ ", " ♠ x"]), DEFAULT_SOLID_GLYPHS);
+
+        assert!(collision.is_solid(2, 0));
+        assert!(collision.is_solid(1, 1));
+        assert!(!collision.is_solid(3, 1)); // Not in the solid set
+        assert!(!collision.is_solid(0, 0));
+    }
+
+    #[test]
+    fn collision_layer_marks_every_non_space_cell() {
+        let collision = CollisionMap::from_collision_layer(&layer(&["x  ", " .."]));
+
+        assert!(collision.is_solid(0, 0));
+        assert!(collision.is_solid(2, 1));
+        assert!(!collision.is_solid(1, 0));
+    }
+
+    #[test]
+    fn cells_outside_the_room_are_free() {
+        let collision = CollisionMap::from_collision_layer(&layer(&["xx", "xx"]));
+
+        assert!(!collision.is_solid(-1, 0));
+        assert!(!collision.is_solid(0, -1));
+        assert!(!collision.is_solid(2, 0));
+        assert!(!collision.is_solid(0, 2));
+    }
+
+    #[test]
+    fn footprint_covers_blank_sprite_cells() {
+        let footprint = Footprint::from_sprite(&sprite(&[" o ", "/|\\"]));
+        let collision = CollisionMap::from_collision_layer(&layer(&["x   x", "     ", "     "]));
+
+        assert!(collision.collides(&footprint, 0, 0)); // Wall under the blank corner
+        assert!(collision.collides(&footprint, 3, 0));
+        assert!(!collision.collides(&footprint, 1, 1));
+    }
+
+    #[test]
+    fn whole_footprint_is_checked() {
+        let footprint = Footprint::from_sprite(&sprite(&[" ╭#╮ ", " ○ ○ ", " │u│ ", " ╰▅╯ ", " ╭▒╮ ", " ▗▒▖ ", " @ @ "]));
+        let mut rows = vec!["          "; 8];
+        rows[7] = "   x      "; // Only the feet row reaches this cell
+        let collision = CollisionMap::from_collision_layer(&layer(&rows));
+
+        assert!(collision.collides(&footprint, 0, 1));
+        assert!(!collision.collides(&footprint, 0, 0));
+        assert!(collision.collides(&footprint, 1, 1)); // Cell between the feet is covered too
+    }
+
+    #[test]
+    fn moves_into_walls_are_blocked_but_moves_out_are_not() {
+        let footprint = Footprint::from_sprite(&sprite(&["oo"]));
+        let collision = CollisionMap::from_collision_layer(&layer(&["   xx   "]));
+
+        assert!(collision.blocks_move(&footprint, (1, 0), (2, 0)));
+        assert!(!collision.blocks_move(&footprint, (0, 0), (1, 0)));
+        assert!(!collision.blocks_move(&footprint, (3, 0), (4, 0))); // Out of the wall
+        assert!(!collision.blocks_move(&footprint, (4, 0), (5, 0)));
+    }
+
+    #[test]
+    fn moves_further_into_walls_are_blocked() {
+        let footprint = Footprint::from_sprite(&sprite(&["oo"]));
+        let collision = CollisionMap::from_collision_layer(&layer(&["   xx   "]));
+
+        assert!(collision.blocks_move(&footprint, (2, 0), (3, 0))); // Already overlapping one wall cell
+        assert!(collision.blocks_move(&footprint, (4, 0), (3, 0)));
+    }
+}
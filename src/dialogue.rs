@@ -0,0 +1,427 @@
+use std::collections::{HashMap, HashSet}; // Import HashMap and HashSet collections
+use std::fs; // Import file system access
+use std::path::Path; // Import path handling
+
+use crate::map::MapError; // Import data file errors
+
+/// Link target that ends the conversation
+pub const END: &str = "end";
+const SPEAKER_COLOR: &str = "cyan"; // Markup color of the speaker's name
+
+/// A game flag that must be set (or clear) for a link to be followed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Condition {
+    pub flag: String,
+    pub is_set: bool,
+}
+
+/// A jump to another node, only taken when all its conditions hold
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Link {
+    pub target: String,
+    pub conditions: Vec<Condition>,
+}
+
+/// An answer the player can pick
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Choice {
+    pub text: String,
+    pub link: Link,
+}
+
+/// One line of a conversation and what can follow it
+#[derive(Debug, Default, PartialEq)]
+pub struct DialogueNode {
+    pub speaker: Option<String>,
+    pub text: Vec<String>, // Paragraphs, shown one after the other
+    pub choices: Vec<Choice>,
+    pub next: Vec<Link>, // Followed when the node has no choices available
+    pub set_flags: Vec<String>, // Flags set when the node is reached
+    pub clear_flags: Vec<String>, // Flags cleared when the node is reached
+    pub shop: Option<String>, // Shop opened once the node has been shown, ending the conversation
+}
+
+/// A conversation loaded from a text file: entry points plus named nodes
+///
+/// The file format follows the map files:
+///
+/// ```text
+/// # Comments start with '#'
+/// start = welcome_back if met_keeper   <- first entry point whose conditions hold
+/// start = greeting
+///
+/// [greeting]                           <- starts a node
+/// speaker = Sheep
+/// text = Baa! Welcome to the shop.     <- may repeat, one paragraph each
+/// set = met_keeper                     <- also `clear = flag`
+/// choice = Who are you? -> about if !asked_name
+/// choice = Goodbye. -> end
+/// next = greeting                      <- taken on confirm when there are no choices
+/// shop = sheep_shop                     <- opens a shop instead, ending the conversation
+/// ```
+///
+/// Conditions are flag names, optionally negated with '!', separated by
+/// spaces; all of them must hold. The target `end` closes the conversation.
+#[derive(Debug, PartialEq)]
+pub struct Dialogue {
+    pub start: Vec<Link>,
+    pub nodes: HashMap<String, DialogueNode>,
+}
+
+/// A conversation in progress
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Conversation {
+    pub dialogue: String, // Name of the dialogue being held
+    pub node: String, // Node currently shown
+    pub selected: usize, // Index into the available choices
+}
+
+impl Dialogue {
+    /// Load every `*.dialogue` file in a directory, keyed by file name
+    pub fn load_all(dialogues_dir: &Path) -> Result<HashMap<String, Dialogue>, MapError> {
+        let io_error = |error| MapError::Io {
+            path: dialogues_dir.to_path_buf(),
+            error,
+        };
+
+        let mut paths = Vec::new();
+        for entry in fs::read_dir(dialogues_dir).map_err(io_error)? {
+            let path = entry.map_err(io_error)?.path();
+            if path.extension().is_some_and(|extension| extension == "dialogue") {
+                paths.push(path);
+            }
+        }
+        paths.sort(); // Load in a stable order so errors are reproducible
+
+        let mut dialogues = HashMap::new();
+        for path in paths {
+            let name = path.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+            let source = fs::read_to_string(&path).map_err(|error| MapError::Io {
+                path: path.clone(),
+                error,
+            })?;
+            dialogues.insert(name, Dialogue::parse(&source, &path)?);
+        }
+        Ok(dialogues)
+    }
+
+    /// Parse dialogue file contents; `path` is only used in error messages
+    pub fn parse(source: &str, path: &Path) -> Result<Dialogue, MapError> {
+        let mut dialogue = Dialogue {
+            start: Vec::new(),
+            nodes: HashMap::new(),
+        };
+        let mut current_node: Option<(String, DialogueNode)> = None;
+        let mut link_lines = Vec::new(); // Every link with its line, checked once all nodes are known
+
+        for (index, line) in source.lines().enumerate() {
+            let line_number = index + 1;
+            let syntax_error = |message: String| MapError::Syntax {
+                path: path.to_path_buf(),
+                line: line_number,
+                message,
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue; // Skip blank lines and comments
+            }
+
+            if let Some(name) = line.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+                // A new node section, which finishes the previous one
+                if let Some((name, node)) = current_node.take() {
+                    dialogue.nodes.insert(name, node);
+                }
+                let name = name.trim().to_string();
+                if dialogue.nodes.contains_key(&name) || name == END {
+                    return Err(syntax_error(format!("duplicate node `{}`", name)));
+                }
+                current_node = Some((name, DialogueNode::default()));
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| syntax_error(format!("expected `key = value`, found `{}`", line)))?;
+            let (key, value) = (key.trim(), value.trim());
+
+            let Some((_, node)) = current_node.as_mut() else {
+                if key != "start" {
+                    return Err(syntax_error(format!("unknown dialogue property `{}`", key)));
+                }
+                let link = parse_link(value).ok_or_else(|| syntax_error(format!("invalid link `{}`", value)))?;
+                link_lines.push((link.target.clone(), line_number));
+                dialogue.start.push(link);
+                continue;
+            };
+
+            match key {
+                "speaker" => node.speaker = Some(value.to_string()),
+                "text" => node.text.push(value.to_string()),
+                "set" => node.set_flags.push(value.to_string()),
+                "clear" => node.clear_flags.push(value.to_string()),
+                "shop" => node.shop = Some(value.to_string()),
+                "next" => {
+                    let link = parse_link(value).ok_or_else(|| syntax_error(format!("invalid link `{}`", value)))?;
+                    link_lines.push((link.target.clone(), line_number));
+                    node.next.push(link);
+                }
+                "choice" => {
+                    let (text, link) = value
+                        .rsplit_once("->")
+                        .ok_or_else(|| syntax_error(format!("expected `text -> node`, found `{}`", value)))?;
+                    let link = parse_link(link).ok_or_else(|| syntax_error(format!("invalid link `{}`", link.trim())))?;
+                    link_lines.push((link.target.clone(), line_number));
+                    node.choices.push(Choice {
+                        text: text.trim().to_string(),
+                        link,
+                    });
+                }
+                _ => return Err(syntax_error(format!("unknown node property `{}`", key))),
+            }
+        }
+
+        if let Some((name, node)) = current_node.take() {
+            dialogue.nodes.insert(name, node);
+        }
+
+        // Check that every link leads somewhere
+        if dialogue.start.is_empty() {
+            return Err(MapError::Syntax {
+                path: path.to_path_buf(),
+                line: 1,
+                message: "dialogue has no `start`".to_string(),
+            });
+        }
+        for (target, line) in link_lines {
+            if target != END && !dialogue.nodes.contains_key(&target) {
+                return Err(MapError::Syntax {
+                    path: path.to_path_buf(),
+                    line,
+                    message: format!("unknown node `{}`", target),
+                });
+            }
+        }
+
+        Ok(dialogue)
+    }
+}
+
+impl Link {
+    /// Whether every condition of the link holds
+    pub fn is_open(&self, flags: &HashSet<String>) -> bool {
+        self.conditions
+            .iter()
+            .all(|condition| flags.contains(&condition.flag) == condition.is_set)
+    }
+}
+
+impl Conversation {
+    /// Start a dialogue at its first open entry point, or `None` if every entry point ends it
+    pub fn start(name: &str, dialogue: &Dialogue, flags: &mut HashSet<String>) -> Option<Conversation> {
+        let link = dialogue.start.iter().find(|link| link.is_open(flags))?;
+        Conversation::enter(name, &link.target, dialogue, flags)
+    }
+
+    /// The choices of the current node whose conditions hold
+    pub fn available_choices<'a>(&self, dialogue: &'a Dialogue, flags: &HashSet<String>) -> Vec<&'a Choice> {
+        dialogue.nodes[&self.node]
+            .choices
+            .iter()
+            .filter(|choice| choice.link.is_open(flags))
+            .collect()
+    }
+
+    /// Move the selection up or down by `offset` choices, wrapping around
+    pub fn move_selection(&mut self, offset: isize, dialogue: &Dialogue, flags: &HashSet<String>) {
+        let choice_count = self.available_choices(dialogue, flags).len() as isize;
+        if choice_count > 0 {
+            self.selected = (self.selected as isize + offset).rem_euclid(choice_count) as usize;
+        }
+    }
+
+    /// Take the selected choice, or the first open `next` link without choices;
+    /// returns `None` once the conversation is over
+    pub fn confirm(&self, dialogue: &Dialogue, flags: &mut HashSet<String>) -> Option<Conversation> {
+        let choices = self.available_choices(dialogue, flags);
+        let target = match choices.get(self.selected) {
+            Some(choice) => choice.link.target.clone(),
+            None => dialogue.nodes[&self.node]
+                .next
+                .iter()
+                .find(|link| link.is_open(flags))?
+                .target
+                .clone(),
+        };
+        Conversation::enter(&self.dialogue, &target, dialogue, flags)
+    }
+
+    /// The paragraphs of the current node, the first one headed by the speaker's name in color
+    pub fn node_texts(&self, dialogue: &Dialogue) -> Vec<String> {
+        let node = &dialogue.nodes[&self.node];
+        let mut texts: Vec<String> = node.text.clone();
+        if let (Some(speaker), Some(first)) = (&node.speaker, texts.first_mut()) {
+            *first = format!("{{{}}}{}{{/}}: {}", SPEAKER_COLOR, speaker, first);
+        }
+        texts
+    }
+
+    /// The available choices as textbox lines, with a marker on the selected one
+    pub fn choice_lines(&self, dialogue: &Dialogue, flags: &HashSet<String>) -> Vec<String> {
+        self.available_choices(dialogue, flags)
+            .iter()
+            .enumerate()
+            .map(|(index, choice)| {
+                let marker = if index == self.selected { '>' } else { ' ' };
+                format!("{} {}", marker, choice.text)
+            })
+            .collect()
+    }
+
+    // Function to move to a node and apply its effects on the flags
+    fn enter(name: &str, target: &str, dialogue: &Dialogue, flags: &mut HashSet<String>) -> Option<Conversation> {
+        if target == END {
+            return None;
+        }
+        let node = &dialogue.nodes[target];
+        for flag in &node.set_flags {
+            flags.insert(flag.clone());
+        }
+        for flag in &node.clear_flags {
+            flags.remove(flag);
+        }
+        Some(Conversation {
+            dialogue: name.to_string(),
+            node: target.to_string(),
+            selected: 0,
+        })
+    }
+}
+
+// Function to parse `node` or `node if flag !other_flag`
+fn parse_link(value: &str) -> Option<Link> {
+    let mut words = value.split_whitespace();
+    let target = words.next()?.to_string();
+    let mut conditions = Vec::new();
+    match words.next() {
+        None => {}
+        Some("if") => {
+            for word in words {
+                let (flag, is_set) = match word.strip_prefix('!') {
+                    Some(flag) => (flag, false),
+                    None => (word, true),
+                };
+                if flag.is_empty() {
+                    return None;
+                }
+                conditions.push(Condition {
+                    flag: flag.to_string(),
+                    is_set,
+                });
+            }
+            if conditions.is_empty() {
+                return None;
+            }
+        }
+        Some(_) => return None,
+    }
+    Some(Link { target, conditions })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHOP: &str = "
+start = again if met
+start = hello
+
+[hello]
+speaker = Sheep
+text = Baa!
+set = met
+choice = Buy -> again
+choice = Ask -> secret if !asked
+choice = Leave -> end
+
+[again]
+text = Back again?
+next = end
+
+[secret]
+set = asked
+next = again
+";
+
+    fn parse(source: &str) -> Dialogue {
+        Dialogue::parse(source, Path::new("test.dialogue")).unwrap()
+    }
+
+    #[test]
+    fn links_and_conditions_are_parsed() {
+        let dialogue = parse(SHOP);
+
+        assert_eq!(dialogue.start.len(), 2);
+        assert_eq!(
+            dialogue.nodes["hello"].choices[1],
+            Choice {
+                text: "Ask".to_string(),
+                link: Link {
+                    target: "secret".to_string(),
+                    conditions: vec![Condition {
+                        flag: "asked".to_string(),
+                        is_set: false,
+                    }],
+                },
+            }
+        );
+        assert_eq!(dialogue.nodes["again"].next[0].target, END);
+    }
+
+    #[test]
+    fn unknown_targets_name_the_line() {
+        let error = Dialogue::parse("start = hello\n\n[hello]\nchoice = Go -> nowhere\n", Path::new("x.dialogue"))
+            .unwrap_err();
+
+        assert_eq!(error.to_string(), "x.dialogue:4: unknown node `nowhere`");
+    }
+
+    #[test]
+    fn effects_change_the_entry_point_and_choices() {
+        let dialogue = parse(SHOP);
+        let mut flags = HashSet::new();
+
+        let conversation = Conversation::start("shop", &dialogue, &mut flags).unwrap();
+        assert_eq!(conversation.node, "hello");
+        assert!(flags.contains("met"));
+        assert_eq!(conversation.node_texts(&dialogue), vec!["{cyan}Sheep{/}: Baa!"]);
+        assert_eq!(conversation.choice_lines(&dialogue, &flags), vec!["> Buy", "  Ask", "  Leave"]);
+
+        let mut conversation = conversation;
+        conversation.move_selection(1, &dialogue, &flags);
+        let conversation = conversation.confirm(&dialogue, &mut flags).unwrap();
+        assert_eq!(conversation.node, "secret");
+        let conversation = conversation.confirm(&dialogue, &mut flags).unwrap();
+        assert_eq!(conversation.node, "again");
+        assert_eq!(conversation.confirm(&dialogue, &mut flags), None);
+
+        // Once met and asked, the conversation starts elsewhere and hides the question
+        let conversation = Conversation::start("shop", &dialogue, &mut flags).unwrap();
+        assert_eq!(conversation.node, "again");
+        let hello = Conversation {
+            node: "hello".to_string(),
+            ..conversation
+        };
+        assert_eq!(hello.available_choices(&dialogue, &flags).len(), 2);
+    }
+
+    #[test]
+    fn selection_wraps_around() {
+        let dialogue = parse(SHOP);
+        let mut flags = HashSet::new();
+        let mut conversation = Conversation::start("shop", &dialogue, &mut flags).unwrap();
+
+        conversation.move_selection(-1, &dialogue, &flags);
+
+        assert_eq!(conversation.selected, 2);
+    }
+}
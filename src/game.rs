@@ -0,0 +1,1262 @@
+use std::collections::{HashMap, HashSet}; // Import HashMap and HashSet collections
+use std::path::{Path, PathBuf}; // Import path handling
+
+use crate::collision::Footprint; // Import character footprints
+use crate::dialogue::{Conversation, Dialogue}; // Import the dialogue engine
+use crate::inventory::{load_items, Inventory, ItemDefinition, INVENTORY_SLOTS}; // Import items and the inventory
+use crate::map::{GameMap, Layer, MapError}; // Import map file handling
+use crate::message_log::MessageLog; // Import the message history
+use crate::movement::CharacterPosition; // Import character movement
+use crate::object::{ObjectAction, RoomObject}; // Import interactable objects
+use crate::parser::{is_called, parse, Action}; // Import the command parser
+use crate::render::{HISTORY_FRAME, INVENTORY_FRAME, TEXTBOX_FRAME}; // Import the screen layout
+use crate::save::{
+    autosave_path, play_time, read_save, read_slot, slot_path, write_autosave, write_slot, SaveData, SaveError, SlotMenu,
+    SlotMenuMode, AUTOSAVE_ROTATIONS,
+}; // Import saved games
+use crate::shop::{Shop, Trade, TradeEntry}; // Import shops and trading
+use crate::world::{Direction, World}; // Import the room graph
+use crate::textbox::{Textbox, TextboxView}; // Import the typewriter textbox
+use crate::{CHARACTER_SPEED, SAVES_DIR, TEXT_AREA_WIDTH, TEXT_REVEAL_SPEED}; // Import shared constants
+
+pub const TICKS_PER_SECOND: u32 = 60; // Simulation rate of the game
+pub const TICK_DURATION: f32 = 1.0 / TICKS_PER_SECOND as f32; // Simulated seconds per tick
+const REVEAL_RADIUS: i32 = 6; // Distance around the character that clears the fog
+const TALK_REACH: i32 = 2; // Distance from the character a dialogue spot can be talked to from
+const AUTOSAVE_INTERVAL: u64 = 60 * TICKS_PER_SECOND as u64; // Ticks between autosaves, besides those on entering a room
+pub const INPUT_LINE_LENGTH: usize = TEXT_AREA_WIDTH as usize - 2; // Characters typed on the input line, besides the prompt and cursor
+
+/// One-shot actions triggered by key presses, applied on the next tick
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Command {
+    ScrollUp,
+    ScrollDown,
+    Confirm, // Talk, or take the selected choice of a conversation
+    SelectPrevious, // Select the previous choice of a conversation
+    SelectNext, // Select the next choice of a conversation
+    ToggleHistory, // Open or close the message history
+    OpenInput, // Start typing a command on the input line
+    Type(char), // Type a character on the input line
+    Erase, // Erase the last character of the input line
+    Submit, // Carry out the command on the input line
+    CloseInput, // Stop typing commands
+    ToggleInventory, // Open or close the inventory screen
+    Drop, // Drop the selected stack of items on the inventory screen
+    ToggleSaveMenu, // Open or close the menu of slots to save the game in
+    ToggleLoadMenu, // Open or close the menu of slots to load a game from
+    Click(i32, i32), // Click a cell (column, row) of the screen grid: walk there, or take the choice shown there
+}
+
+/// Everything the simulation reads from the player for one tick
+#[derive(Default)]
+pub struct TickInput {
+    pub direction: (f32, f32), // Direction of the movement keys held down
+    pub commands: Vec<Command>,
+}
+
+/// The whole state of a running game, advanced one fixed tick at a time
+pub struct GameState {
+    pub world: World,
+    pub character_picture: Layer,
+    pub character_footprint: Footprint,
+    pub character_position: CharacterPosition,
+    pub character_speed_multiplier: f32,
+    pub facing: Direction, // Direction the character last walked in
+    pub textbox: Textbox,
+    pub dialogues: HashMap<String, Dialogue>,
+    pub flags: HashSet<String>, // Game flags set and tested by dialogues
+    pub items: HashMap<String, ItemDefinition>,
+    pub inventory: Inventory,
+    pub is_inventory_open: bool, // The inventory screen is shown and the game is paused
+    pub inventory_selection: usize, // Slot selected on the inventory screen
+    pub shops: HashMap<String, Shop>,
+    pub trade: Option<Trade>, // The shop visit in progress, its menu shown in the textbox
+    pub conversation: Option<Conversation>,
+    pub input_line: Option<String>, // The command being typed, while the input line is open
+    pub walk_direction: Option<Direction>, // Direction the character walks in after a typed GO
+    pub walk_target: Option<(i32, i32)>, // Cell the character walks to stand on after a click
+    pub pointer_cell: Option<(i32, i32)>, // Cell of the screen grid under the mouse pointer, if any
+    pub ticks: u64, // Ticks simulated since the game started
+    pub message_log: MessageLog,
+    pub is_history_open: bool, // The message history covers the screen and the game is paused
+    pub slot_menu: Option<SlotMenu>, // The menu of save slots, while it is open and the game is paused
+    pub saves_dir: PathBuf, // Directory the save slots and autosaves are kept in
+    pub is_autosaving: bool, // Autosave every so often and on entering a room, off for scripted runs
+    pub last_autosave: u64, // Tick of the last autosave, or of the start of the game
+    pub is_running: bool,
+}
+
+impl GameState {
+    /// Start a new game in the world's start room
+    pub fn new(
+        world: World,
+        character_picture: Layer,
+        dialogues: HashMap<String, Dialogue>,
+        items: HashMap<String, ItemDefinition>,
+        shops: HashMap<String, Shop>,
+    ) -> GameState {
+        let character_footprint = Footprint::from_sprite(&character_picture.rows);
+        let mut game_state = GameState {
+            world,
+            character_picture,
+            character_footprint,
+            character_position: CharacterPosition::new(7, 8),
+            character_speed_multiplier: 1.5,
+            facing: Direction::South,
+            textbox: Textbox::new(TEXT_REVEAL_SPEED),
+            dialogues,
+            flags: HashSet::new(),
+            items,
+            inventory: Inventory::default(),
+            is_inventory_open: false,
+            inventory_selection: 0,
+            shops,
+            trade: None,
+            conversation: None,
+            input_line: None,
+            walk_direction: None,
+            walk_target: None,
+            pointer_cell: None,
+            ticks: 0,
+            message_log: MessageLog::default(),
+            is_history_open: false,
+            slot_menu: None,
+            saves_dir: PathBuf::from(SAVES_DIR),
+            is_autosaving: false,
+            last_autosave: 0,
+            is_running: true,
+        };
+        for text in [
+            "\"Welcome   to The Fabricof! Welcome to The Fabricof! Welcome to The Fabricof! Welcome to The Fabricof!",
+            "       ",
+            "This is the very first text line of the game! This is the very first text line of the game!",
+            "Please, do not give up, it will be more, soon! Please, do not give up, it will be more, soon!",
+            "And this line is for the bablabla, blablablab and ablablabla!",
+            ".",
+        ] {
+            game_state.say(text);
+        }
+        game_state.reveal_around_character();
+        game_state
+    }
+
+    /// Load the rooms and the character from the maps directory and start a new game
+    pub fn load(maps_dir: &Path, start_room: &str) -> Result<GameState, MapError> {
+        let world = World::load(&maps_dir.join("rooms"), start_room)?;
+        let mut character_map = GameMap::load(&maps_dir.join("character.map"))?;
+        let character_picture = character_map.take_layer("character")?;
+        let dialogues = Dialogue::load_all(&maps_dir.join("dialogues"))?;
+        let items = load_items(&maps_dir.join("items.txt"))?;
+        let shops = Shop::load_all(&maps_dir.join("shops"))?;
+
+        // Check that every shop trades in items that exist, and every dialogue opens a shop that does
+        for (shop_name, shop) in &shops {
+            let traded = shop.stock.iter().map(|entry| &entry.item).chain(shop.buys.iter().map(|(item, _)| item));
+            for item in traded {
+                if !items.contains_key(item) {
+                    return Err(MapError::UnknownItem {
+                        path: maps_dir.join("shops").join(format!("{}.shop", shop_name)),
+                        item: item.clone(),
+                    });
+                }
+            }
+        }
+        for (dialogue_name, dialogue) in &dialogues {
+            for shop in dialogue.nodes.values().filter_map(|node| node.shop.as_ref()) {
+                if !shops.contains_key(shop) {
+                    return Err(MapError::UnknownShop {
+                        path: maps_dir.join("dialogues").join(format!("{}.dialogue", dialogue_name)),
+                        shop: shop.clone(),
+                    });
+                }
+            }
+        }
+
+        // Check that every room and object talks with a dialogue that exists, and holds items that do
+        for (room_name, room) in world.rooms() {
+            for object in &room.objects {
+                if let ObjectAction::Item { item, .. } = &object.action {
+                    if !items.contains_key(item) {
+                        return Err(MapError::UnknownItem {
+                            path: maps_dir.join("rooms").join(format!("{}.map", room_name)),
+                            item: item.clone(),
+                        });
+                    }
+                }
+            }
+            let object_dialogues = room.objects.iter().filter_map(|object| match &object.action {
+                ObjectAction::Talk(dialogue) => Some(dialogue),
+                _ => None,
+            });
+            for dialogue in room.dialogue.iter().map(|(dialogue, _)| dialogue).chain(object_dialogues) {
+                if !dialogues.contains_key(dialogue) {
+                    return Err(MapError::UnknownDialogue {
+                        path: maps_dir.join("rooms").join(format!("{}.map", room_name)),
+                        dialogue: dialogue.clone(),
+                    });
+                }
+            }
+        }
+
+        Ok(GameState::new(world, character_picture, dialogues, items, shops))
+    }
+
+    /// Advance the game by one tick of `TICK_DURATION` seconds
+    pub fn update(&mut self, input: &TickInput) {
+        for command in &input.commands {
+            match command {
+                Command::ToggleHistory => {
+                    self.is_history_open = !self.is_history_open;
+                    self.message_log.scroll_to_end();
+                }
+                // The open history takes the scroll keys and ignores the rest
+                Command::ScrollUp if self.is_history_open => self.message_log.page_up(HISTORY_FRAME.visible_lines()),
+                Command::ScrollDown if self.is_history_open => self.message_log.page_down(HISTORY_FRAME.visible_lines()),
+                _ if self.is_history_open => {}
+                Command::ToggleInventory => self.is_inventory_open = !self.is_inventory_open,
+                // The open inventory takes the selection keys and ignores the rest
+                Command::SelectPrevious if self.is_inventory_open => self.move_inventory_selection(-1),
+                Command::SelectNext if self.is_inventory_open => self.move_inventory_selection(1),
+                Command::Confirm if self.is_inventory_open => self.use_item(self.inventory_selection),
+                Command::Drop if self.is_inventory_open => self.drop_item(self.inventory_selection),
+                _ if self.is_inventory_open => {}
+                Command::ToggleSaveMenu => self.toggle_slot_menu(SlotMenuMode::Save),
+                Command::ToggleLoadMenu => self.toggle_slot_menu(SlotMenuMode::Load),
+                // The open slot menu takes the selection keys and ignores the rest
+                Command::SelectPrevious if self.slot_menu.is_some() => self.move_slot_selection(-1),
+                Command::SelectNext if self.slot_menu.is_some() => self.move_slot_selection(1),
+                Command::Confirm if self.slot_menu.is_some() => self.use_slot_menu(),
+                _ if self.slot_menu.is_some() => {}
+                Command::Drop => {} // Only the inventory screen drops items
+                Command::OpenInput => self.input_line = Some(self.input_line.take().unwrap_or_default()),
+                Command::Type(char_typed) => self.type_char(*char_typed),
+                Command::Erase => {
+                    if let Some(input_line) = self.input_line.as_mut() {
+                        input_line.pop();
+                    }
+                }
+                Command::Submit => self.submit(),
+                Command::CloseInput => self.input_line = None,
+                Command::ScrollUp => self.textbox.scroll_up(&self.choice_lines()),
+                Command::ScrollDown => self.textbox.scroll_down(),
+                Command::Confirm => self.confirm(),
+                Command::SelectPrevious => self.move_selection(-1),
+                Command::SelectNext => self.move_selection(1),
+                Command::Click(col, row) => self.click((*col, *row)),
+            }
+        }
+
+        // The game is paused while the history, the inventory or the slot menu is open
+        if self.is_history_open || self.is_inventory_open || self.slot_menu.is_some() {
+            return;
+        }
+        self.ticks += 1;
+
+        self.textbox.update(TICK_DURATION);
+
+        // The character stands still while talking or trading
+        if self.conversation.is_some() || self.trade.is_some() {
+            return;
+        }
+
+        // Held keys take over from a walk typed on the input line or clicked
+        if input.direction != (0.0, 0.0) {
+            self.walk_direction = None;
+            self.walk_target = None;
+        }
+        let room_before = self.world.current_room_name().to_string();
+        let walked_from = self.walk_direction.map(|_| (self.character_position.x, self.character_position.y));
+        let clicked_from = self.walk_target.map(|_| (self.character_position.x, self.character_position.y));
+
+        // Move the character in the held direction, and face that way
+        let direction = match (self.walk_direction, self.walk_target) {
+            (Some(walk_direction), _) => walk_direction.offset(),
+            (None, Some(target)) => self.direction_to(target),
+            (None, None) => input.direction,
+        };
+        self.facing = match direction {
+            (x, _) if x < 0.0 => Direction::West,
+            (x, _) if x > 0.0 => Direction::East,
+            (_, y) if y < 0.0 => Direction::North,
+            (_, y) if y > 0.0 => Direction::South,
+            _ => self.facing,
+        };
+        self.character_position.move_by(
+            direction,
+            CHARACTER_SPEED * self.character_speed_multiplier * TICK_DURATION,
+            &self.world.current_room().collision,
+            &self.character_footprint,
+        );
+
+        // Walk into the neighbouring room when the character leaves the landscape
+        let new_cell = self.world.cross_edge(
+            self.character_position.cell(),
+            (self.character_picture.width as i32, self.character_picture.height as i32),
+        );
+        self.character_position.snap_to(new_cell);
+
+        let has_changed_room = room_before != self.world.current_room_name();
+
+        // A typed walk ends in the next room, or where something is in the way
+        if let Some((x, y)) = walked_from {
+            if has_changed_room {
+                self.walk_direction = None;
+            } else if (x, y) == (self.character_position.x, self.character_position.y) {
+                self.walk_direction = None;
+                self.say("Something blocks the way.");
+            }
+        }
+
+        // A clicked walk ends on the clicked cell, in the next room, or where something is in the way
+        if let Some((x, y)) = clicked_from {
+            let has_arrived = self.walk_target.is_some_and(|target| self.direction_to(target) == (0.0, 0.0));
+            if has_changed_room || has_arrived || (x, y) == (self.character_position.x, self.character_position.y) {
+                self.walk_target = None;
+            }
+        }
+
+        self.reveal_around_character();
+
+        // Autosave every so often, and whenever the character enters another room
+        if self.is_autosaving && (has_changed_room || self.ticks.saturating_sub(self.last_autosave) >= AUTOSAVE_INTERVAL) {
+            self.autosave();
+        }
+    }
+
+    /// Carry on with the newest autosave that is intact, as when the game starts
+    ///
+    /// Autosaves that can't be parsed, such as one cut short by a crash while
+    /// it was written, are kept aside as `.damaged` files and the one before
+    /// them is tried instead. Autosaves that fail for another reason, such as
+    /// one written by a newer game or one that can't be opened, are left in
+    /// place and the one before them is tried. Without any autosave the game
+    /// stays as it is.
+    pub fn resume_autosave(&mut self) {
+        let mut damaged_errors = Vec::new();
+        let mut failed_errors = Vec::new();
+        let mut resumed_summary = None;
+        for rotation in 0..AUTOSAVE_ROTATIONS {
+            let path = autosave_path(&self.saves_dir, rotation);
+            match read_save(&path) {
+                Ok(Some(save)) => {
+                    let summary = format!("{}, {}", self.room_display_name(&save.room), play_time(save.ticks));
+                    match self.restore_save(save, &path) {
+                        Ok(()) => {
+                            resumed_summary = Some(summary);
+                            break;
+                        }
+                        Err(error) => failed_errors.push(error),
+                    }
+                }
+                Ok(None) => {}
+                Err(error @ SaveError::Syntax { .. }) => {
+                    let _ = std::fs::rename(&path, path.with_extension("sav.damaged")); // Keep it for a look, out of the rotation
+                    damaged_errors.push(error);
+                }
+                Err(error) => failed_errors.push(error),
+            }
+        }
+
+        // Tell the player once the restored message history is in place
+        for error in damaged_errors {
+            self.say(&format!("{{red}}An autosave is damaged{{/}} and was set aside: {}", error));
+        }
+        for error in failed_errors {
+            self.say(&format!("{{red}}An autosave could not be loaded:{{/}} {}", error));
+        }
+        if let Some(summary) = resumed_summary {
+            self.say(&format!("Welcome back! The game carries on from the autosave in {}.", summary));
+        }
+    }
+
+    /// The cell the character is drawn at
+    pub fn character_cell(&self) -> (i32, i32) {
+        self.character_position.cell()
+    }
+
+    /// The lines shown in the textbox: the revealed messages, then the choices of the conversation
+    pub fn textbox_view(&self) -> TextboxView {
+        let choice_lines = self.choice_lines();
+        let mut view = self.textbox.view(&choice_lines);
+        view.input_line = self.input_line.clone();
+
+        // A conversation line without choices also waits for the player to continue
+        let conversation_waits = self.conversation.is_some() && choice_lines.is_empty() && self.textbox.is_finished();
+        view.shows_continue_indicator |= conversation_waits && self.textbox.is_indicator_lit();
+        view
+    }
+
+    /// The object of the current room under the mouse pointer, on the
+    /// revealed landscape, while no screen covers it
+    pub fn hovered_object(&self) -> Option<&RoomObject> {
+        let (col, row) = self.pointer_cell?;
+        if self.is_history_open || self.is_inventory_open || self.slot_menu.is_some() || row >= TEXTBOX_FRAME.top as i32 {
+            return None;
+        }
+        let room = self.world.current_room();
+        if !room.revealed_positions.contains(&(row as usize, col as usize)) {
+            return None;
+        }
+        room.objects.iter().find(|object| object.cells.contains(&(col, row)))
+    }
+
+    /// The message history covering the screen, while it is open
+    pub fn history_view(&self) -> Option<TextboxView> {
+        self.is_history_open
+            .then(|| self.message_log.view(HISTORY_FRAME.visible_lines()))
+    }
+
+    /// The inventory screen, while it is open
+    pub fn inventory_view(&self) -> Option<TextboxView> {
+        self.is_inventory_open.then(|| {
+            self.inventory
+                .view(self.inventory_selection, &self.items, INVENTORY_FRAME.text_width(), INVENTORY_FRAME.visible_lines())
+        })
+    }
+
+    /// The menu of save slots, while it is open
+    pub fn slot_menu_view(&self) -> Option<TextboxView> {
+        self.slot_menu.as_ref().map(SlotMenu::view)
+    }
+
+    // Function to show a message in the textbox and keep it in the history
+    fn say(&mut self, text: &str) {
+        self.textbox.push(text);
+        self.message_log.record(self.ticks, text);
+    }
+
+    // Function to list the choices of the conversation, or the trade menu, as textbox lines
+    fn choice_lines(&self) -> Vec<String> {
+        if let Some(trade) = &self.trade {
+            return trade.menu_lines(&self.shops[&trade.shop], &self.inventory, &self.items, TEXT_AREA_WIDTH as usize);
+        }
+        match &self.conversation {
+            Some(conversation) => conversation.choice_lines(&self.dialogues[&conversation.dialogue], &self.flags),
+            None => Vec::new(),
+        }
+    }
+
+    // Function to reveal or turn the page of the textbox, or else advance the
+    // conversation or start the one within reach
+    fn confirm(&mut self) {
+        if self.textbox.advance() {
+            return;
+        }
+        if self.trade.is_some() {
+            self.trade_selected();
+            return;
+        }
+        match &self.conversation {
+            Some(conversation) => {
+                let next = conversation.confirm(&self.dialogues[&conversation.dialogue], &mut self.flags);
+                self.show_conversation(next);
+            }
+            None => {
+                if let Some(name) = self.dialogue_within_reach() {
+                    self.start_conversation(&name);
+                } else if let Some(object) = self.object_within_reach().cloned() {
+                    self.use_object(&object);
+                }
+            }
+        }
+    }
+
+    // Function to carry out the action of an object
+    fn use_object(&mut self, object: &RoomObject) {
+        match &object.action {
+            ObjectAction::Describe => self.say(&object.description),
+            ObjectAction::Say(text) => self.say(text),
+            ObjectAction::Talk(dialogue) => self.start_conversation(dialogue),
+            ObjectAction::Item { .. } | ObjectAction::Coins(_) => self.pick_up(object),
+        }
+    }
+
+    // Function to carry out the selected entry of the trade menu
+    fn trade_selected(&mut self) {
+        let Some(trade) = self.trade.as_mut() else {
+            return;
+        };
+        let shop = self.shops.get_mut(&trade.shop).expect("shop was loaded");
+        match trade.selected_entry(shop, &self.inventory) {
+            TradeEntry::Buy(index) => {
+                let (name, price) = (self.items[&shop.stock[index].item].name.clone(), shop.stock[index].price);
+                match shop.buy(index, &mut self.inventory, &self.items) {
+                    Ok(()) => self.say(&format!("You buy {} for {} coins.", name, price)),
+                    Err(error) => self.say(&error.to_string()),
+                }
+            }
+            TradeEntry::Sell(item) => match shop.sell(&item, &mut self.inventory) {
+                Ok(price) => self.say(&format!("You sell {} for {} coins.", self.items[&item].name, price)),
+                Err(error) => self.say(&error.to_string()),
+            },
+            TradeEntry::SwitchMode => trade.switch_mode(),
+            TradeEntry::Leave => {
+                let farewell = shop.farewell.clone();
+                self.trade = None;
+                if let Some(farewell) = farewell {
+                    self.say(&farewell);
+                }
+            }
+        }
+    }
+
+    // Function to pick up as many of the items an object holds as fit in the
+    // inventory, or the coins it holds into the wallet
+    fn pick_up(&mut self, object: &RoomObject) {
+        let room = self.world.current_room_mut();
+        let index = room.objects.iter().position(|room_object| room_object == object).expect("object is in the room");
+        let (item, quantity) = match &object.action {
+            ObjectAction::Item { item, quantity } => (item, quantity),
+            ObjectAction::Coins(coins) => {
+                room.objects.remove(index);
+                self.inventory.coins += coins;
+                self.say(&format!("You pick up {} coins.", coins));
+                return;
+            }
+            _ => return,
+        };
+        let left_over = self.inventory.add(item, *quantity, &self.items);
+        if left_over == *quantity {
+            self.say("You can't carry any more.");
+            return;
+        }
+
+        let room = self.world.current_room_mut();
+        if left_over == 0 {
+            room.objects.remove(index);
+        } else {
+            room.objects[index].action = ObjectAction::Item {
+                item: item.clone(),
+                quantity: left_over,
+            };
+        }
+        let name = &self.items[item].name;
+        self.say(&format!("You pick up {} x{}.", name, quantity - left_over));
+    }
+
+    // Function to move the selected slot of the inventory screen
+    fn move_inventory_selection(&mut self, offset: isize) {
+        self.inventory_selection = (self.inventory_selection as isize + offset).rem_euclid(INVENTORY_SLOTS as isize) as usize;
+    }
+
+    // Function to use the items of a slot, leaving the inventory screen to show what happens
+    fn use_item(&mut self, slot: usize) {
+        let Some(stack) = self.inventory.stacks.get(slot) else {
+            return;
+        };
+        let item = &self.items[&stack.item];
+        let text = match &item.use_text {
+            Some(use_text) => use_text.clone(),
+            None => format!("You can't use the {} here.", item.name.to_lowercase()),
+        };
+        self.is_inventory_open = false;
+        self.say(&text);
+    }
+
+    // Function to drop the items of a slot at the character's feet, leaving the inventory screen
+    fn drop_item(&mut self, slot: usize) {
+        if slot >= self.inventory.stacks.len() {
+            return;
+        }
+        let stack = self.inventory.stacks.remove(slot);
+        let item = &self.items[&stack.item];
+        let (x, y) = self.character_cell();
+        let feet = (x + self.character_picture.width as i32 / 2, y + self.character_picture.height as i32 - 1);
+        let object = RoomObject {
+            glyph: item.glyph,
+            name: item.name.clone(),
+            description: item.description.clone(),
+            action: ObjectAction::Item {
+                item: stack.item.clone(),
+                quantity: stack.quantity,
+            },
+            cells: HashSet::from([feet]),
+        };
+        let text = format!("You drop {} x{}.", item.name, stack.quantity);
+        self.world.current_room_mut().objects.push(object);
+        self.is_inventory_open = false;
+        self.say(&text);
+    }
+
+    // Function to open the slot menu to save or load, or close it if it is already open that way
+    fn toggle_slot_menu(&mut self, mode: SlotMenuMode) {
+        if self.slot_menu.as_ref().is_some_and(|slot_menu| slot_menu.mode == mode) {
+            self.slot_menu = None;
+            return;
+        }
+        self.slot_menu = Some(SlotMenu::open(mode, &self.saves_dir, |room_file| self.room_display_name(room_file)));
+    }
+
+    // Function to find the name shown for a room's file, the file name itself if there is no such room
+    fn room_display_name(&self, room_file: &str) -> String {
+        self.world
+            .rooms()
+            .find(|(name, _)| name.as_str() == room_file)
+            .map_or_else(|| room_file.to_string(), |(_, room)| room.name.clone())
+    }
+
+    // Function to move the selected slot of the slot menu
+    fn move_slot_selection(&mut self, offset: isize) {
+        if let Some(slot_menu) = self.slot_menu.as_mut() {
+            slot_menu.move_selection(offset);
+        }
+    }
+
+    // Function to save to or load from the selected slot, closing the slot menu
+    fn use_slot_menu(&mut self) {
+        let Some(slot_menu) = self.slot_menu.take() else {
+            return;
+        };
+        match slot_menu.mode {
+            SlotMenuMode::Save => self.save_game(slot_menu.selected + 1),
+            SlotMenuMode::Load => self.load_game(slot_menu.selected + 1),
+        }
+    }
+
+    // Function to save the game in a slot, numbered from 1
+    fn save_game(&mut self, slot: usize) {
+        match write_slot(&self.saves_dir, slot, &SaveData::capture(self)) {
+            Ok(_) => self.say(&format!("Game saved in slot {}.", slot)),
+            Err(error) => self.say(&format!("{{red}}Saving failed:{{/}} {}", error)),
+        }
+    }
+
+    // Function to load the game in a slot, numbered from 1, starting afresh
+    // from the saved state with an empty textbox
+    fn load_game(&mut self, slot: usize) {
+        let restored = match read_slot(&self.saves_dir, slot) {
+            Ok(Some(save)) => self.restore_save(save, &slot_path(&self.saves_dir, slot)).map(|()| true),
+            Ok(None) => Ok(false),
+            Err(error) => Err(error),
+        };
+        match restored {
+            Ok(true) => self.say(&format!("Game loaded from slot {}.", slot)),
+            Ok(false) => self.say(&format!("Slot {} is empty.", slot)),
+            Err(error) => self.say(&format!("{{red}}Loading failed:{{/}} {}", error)),
+        }
+    }
+
+    // Function to carry on from a saved game with an empty textbox, leaving
+    // any conversation, trade or screen open behind
+    fn restore_save(&mut self, save: SaveData, path: &Path) -> Result<(), SaveError> {
+        save.restore(self, path)?;
+        self.textbox = Textbox::new(TEXT_REVEAL_SPEED);
+        self.conversation = None;
+        self.trade = None;
+        self.walk_direction = None;
+        self.walk_target = None;
+        self.is_inventory_open = false;
+        self.inventory_selection = 0;
+        self.last_autosave = self.ticks;
+        Ok(())
+    }
+
+    // Function to save the game as the newest autosave, only saying so if it fails
+    fn autosave(&mut self) {
+        self.last_autosave = self.ticks;
+        if let Err(error) = write_autosave(&self.saves_dir, &SaveData::capture(self)) {
+            self.say(&format!("{{red}}Autosaving failed:{{/}} {}", error));
+        }
+    }
+
+    // Function to start the conversation held by a dialogue
+    fn start_conversation(&mut self, name: &str) {
+        let conversation = Conversation::start(name, &self.dialogues[name], &mut self.flags);
+        self.show_conversation(conversation);
+    }
+
+    // Function to make a conversation the current one and show what its node
+    // says, opening the trade menu if the node leads into a shop
+    fn show_conversation(&mut self, conversation: Option<Conversation>) {
+        self.conversation = conversation;
+        let Some(conversation) = &self.conversation else {
+            return;
+        };
+        let dialogue = &self.dialogues[&conversation.dialogue];
+        let shop = dialogue.nodes[&conversation.node].shop.clone();
+        for text in conversation.node_texts(dialogue) {
+            self.say(&text);
+        }
+        if let Some(shop) = shop {
+            self.conversation = None;
+            self.trade = Some(Trade::open(&shop));
+        }
+    }
+
+    // Function to type a character on the input line, leaving out markup braces and control characters
+    fn type_char(&mut self, char_typed: char) {
+        if let Some(input_line) = self.input_line.as_mut() {
+            let is_typeable = !char_typed.is_control() && !matches!(char_typed, '{' | '}');
+            if is_typeable && input_line.chars().count() < INPUT_LINE_LENGTH {
+                input_line.push(char_typed);
+            }
+        }
+    }
+
+    // Function to echo the command on the input line and carry it out, leaving the line open for the next one
+    fn submit(&mut self) {
+        let Some(input_line) = self.input_line.replace(String::new()) else {
+            return;
+        };
+        self.say(&format!("{{gray}}> {}{{/}}", input_line));
+        match parse(&input_line) {
+            Ok(action) => self.perform(action),
+            Err(error) => self.say(&error.to_string()),
+        }
+    }
+
+    // Function to carry out a typed action and answer in the textbox
+    fn perform(&mut self, action: Action) {
+        match action {
+            Action::Look(None) => {
+                let room = self.world.current_room();
+                let exits: Vec<&str> = [Direction::North, Direction::South, Direction::East, Direction::West]
+                    .into_iter()
+                    .filter(|direction| room.exit(*direction).is_some())
+                    .map(Direction::name)
+                    .collect();
+                let mut text = format!("{{yellow}}{}{{/}}. {}", room.name, room.description.as_deref().unwrap_or(""));
+                if !exits.is_empty() {
+                    text.push_str(&format!(" Exits: {}.", exits.join(", ")));
+                }
+                self.say(text.trim_end());
+            }
+            Action::Look(Some(noun)) => match self.object_called(&noun) {
+                Some(object) => self.say(&object.description.clone()),
+                None => self.say(&format!("You see no {} here.", noun)),
+            },
+            Action::Take(noun) => match self.object_called(&noun).cloned() {
+                Some(object) if !matches!(object.action, ObjectAction::Item { .. } | ObjectAction::Coins(_)) => {
+                    self.say(&format!("You can't take the {}.", object.name.to_lowercase()))
+                }
+                Some(object) if self.is_within_reach(&object) => self.pick_up(&object),
+                Some(object) => self.say(&format!("The {} is out of reach.", object.name.to_lowercase())),
+                None => self.say(&format!("You see no {} here.", noun)),
+            },
+            Action::Use(noun) => match self.object_called(&noun).cloned() {
+                Some(object) if self.is_within_reach(&object) => self.use_object(&object),
+                Some(object) => self.say(&format!("The {} is out of reach.", object.name.to_lowercase())),
+                None => match self.carried_slot(&noun) {
+                    Some(slot) => self.use_item(slot),
+                    None => self.say(&format!("You see no {} here.", noun)),
+                },
+            },
+            Action::Drop(noun) => match self.carried_slot(&noun) {
+                Some(slot) => self.drop_item(slot),
+                None => self.say(&format!("You don't have any {}.", noun)),
+            },
+            Action::Go(direction) => {
+                if self.world.current_room().exit(direction).is_some() {
+                    self.walk_direction = Some(direction);
+                    self.walk_target = None;
+                    self.say(&format!("You head {}.", direction.name()));
+                } else {
+                    self.say(&format!("You can't go {} from here.", direction.name()));
+                }
+            }
+            Action::Inventory => {
+                let stacks: Vec<String> = self
+                    .inventory
+                    .stacks
+                    .iter()
+                    .map(|stack| format!("{} x{}", self.items[&stack.item].name, stack.quantity))
+                    .collect();
+                let coins = format!(" You have {} coins.", self.inventory.coins);
+                if stacks.is_empty() {
+                    self.say(&format!("You are carrying nothing.{}", coins));
+                } else {
+                    self.say(&format!("You carry: {}.{}", stacks.join(", "), coins));
+                }
+            }
+            Action::Talk => match self.dialogue_within_reach() {
+                _ if self.conversation.is_some() || self.trade.is_some() => self.say("You are already talking."),
+                Some(name) => self.start_conversation(&name),
+                None => self.say("There is nobody here to talk to."),
+            },
+            Action::Save(slot) => self.save_game(slot),
+            Action::Load(slot) => self.load_game(slot),
+            Action::Help => self.say(
+                "Try LOOK, LOOK TREE, USE TREE, GO EAST, TAKE WOOL, DROP WOOL, INVENTORY, TALK, SAVE 1 or LOAD 1. ESC stops typing.",
+            ),
+        }
+    }
+
+    // Function to react to a click on the screen grid: on the landscape the
+    // character walks there, and in the textbox the choice or trade menu
+    // entry clicked is taken, or else the text goes on
+    fn click(&mut self, (col, row): (i32, i32)) {
+        if row < TEXTBOX_FRAME.top as i32 {
+            if self.conversation.is_none() && self.trade.is_none() {
+                self.walk_direction = None;
+                self.walk_target = Some((col, row));
+            }
+            return;
+        }
+        if row <= TEXTBOX_FRAME.top as i32 || row >= TEXTBOX_FRAME.bottom as i32 {
+            return; // On the frame
+        }
+
+        if self.textbox.advance() {
+            return; // Reveal the rest of the page, or turn it
+        }
+        let choice_lines = self.choice_lines();
+        let entry = self.textbox.footer_entry_at(&choice_lines, (row - TEXTBOX_FRAME.top as i32 - 1) as usize);
+        if let Some(trade) = self.trade.as_mut() {
+            if let Some(index) = entry.filter(|index| *index > 0) {
+                trade.selected = index - 1; // After the title of the menu
+                self.trade_selected();
+            }
+        } else if let Some(conversation) = self.conversation.as_mut() {
+            if let Some(index) = entry {
+                conversation.selected = index;
+                self.confirm();
+            } else if choice_lines.is_empty() {
+                self.confirm(); // Go on with a conversation line without choices
+            }
+        }
+    }
+
+    // Function to find the direction that takes the character to stand on a
+    // cell, with its feet in the middle of its bottom row, slowing down on
+    // the last step so it stops right there
+    fn direction_to(&self, (col, row): (i32, i32)) -> (f32, f32) {
+        let target_x = (col - self.character_picture.width as i32 / 2) as f32;
+        let target_y = (row - self.character_picture.height as i32 + 1) as f32;
+        let step = CHARACTER_SPEED * self.character_speed_multiplier * TICK_DURATION;
+        let axis = |distance: f32| if distance.abs() < 0.001 { 0.0 } else { (distance / step).clamp(-1.0, 1.0) };
+        (axis(target_x - self.character_position.x), axis(target_y - self.character_position.y))
+    }
+
+    // Function to move the selected choice of the conversation, or entry of the trade menu
+    fn move_selection(&mut self, offset: isize) {
+        if let Some(trade) = self.trade.as_mut() {
+            trade.move_selection(offset, &self.shops[&trade.shop], &self.inventory);
+        } else if let Some(conversation) = self.conversation.as_mut() {
+            conversation.move_selection(offset, &self.dialogues[&conversation.dialogue], &self.flags);
+        }
+    }
+
+    // Function to find the dialogue of the current room if its spot is within reach of the character
+    fn dialogue_within_reach(&self) -> Option<String> {
+        let (name, (spot_x, spot_y)) = self.world.current_room().dialogue.as_ref()?;
+        let (x, y) = self.character_cell();
+        let (width, height) = (self.character_picture.width as i32, self.character_picture.height as i32);
+        let within_reach = (x - TALK_REACH..x + width + TALK_REACH).contains(spot_x)
+            && (y - TALK_REACH..y + height + TALK_REACH).contains(spot_y);
+        within_reach.then(|| name.clone())
+    }
+
+    // Function to find the slot of a carried item the player calls `noun`
+    fn carried_slot(&self, noun: &str) -> Option<usize> {
+        self.inventory
+            .stacks
+            .iter()
+            .position(|stack| is_called(&self.items[&stack.item].name, noun))
+    }
+
+    // Function to find the object of the current room the player calls
+    // `noun`, preferring one within reach over others of the same name
+    fn object_called(&self, noun: &str) -> Option<&RoomObject> {
+        let room = self.world.current_room();
+        room.objects
+            .iter()
+            .filter(|object| object.is_called(noun))
+            .find(|object| self.is_within_reach(object))
+            .or_else(|| room.object_called(noun))
+    }
+
+    // Function to find the first object of the current room the character
+    // stands on or faces, counting the cells right in front of the character
+    fn object_within_reach(&self) -> Option<&RoomObject> {
+        self.world
+            .current_room()
+            .objects
+            .iter()
+            .find(|object| self.is_within_reach(object))
+    }
+
+    // Function to check whether the character stands on or faces `object`
+    fn is_within_reach(&self, object: &RoomObject) -> bool {
+        let (x, y) = self.character_cell();
+        let (width, height) = (self.character_picture.width as i32, self.character_picture.height as i32);
+        let (left, top, right, bottom) = match self.facing {
+            Direction::North => (x, y - 1, x + width, y + height),
+            Direction::South => (x, y, x + width, y + height + 1),
+            Direction::East => (x, y, x + width + 1, y + height),
+            Direction::West => (x - 1, y, x + width, y + height),
+        };
+        object.covers_any((top..bottom).flat_map(|row| (left..right).map(move |col| (col, row))))
+    }
+
+    // Function to clear the fog of the current room around the character
+    fn reveal_around_character(&mut self) {
+        let character_cell = self.character_cell();
+        self.world
+            .current_room_mut()
+            .reveal_around(character_cell, REVEAL_RADIUS);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markup::line_text;
+    use crate::{MAPS_DIR, START_ROOM};
+
+    fn new_game() -> GameState {
+        GameState::load(Path::new(MAPS_DIR), START_ROOM).unwrap()
+    }
+
+    fn walk(game_state: &mut GameState, direction: (f32, f32), ticks: usize) {
+        let input = TickInput {
+            direction,
+            commands: Vec::new(),
+        };
+        for _ in 0..ticks {
+            game_state.update(&input);
+        }
+    }
+
+    fn confirm() -> TickInput {
+        TickInput {
+            direction: (0.0, 0.0),
+            commands: vec![Command::Confirm],
+        }
+    }
+
+    fn type_command(game_state: &mut GameState, text: &str) {
+        let mut commands = vec![Command::OpenInput];
+        commands.extend(text.chars().map(Command::Type));
+        commands.push(Command::Submit);
+        game_state.update(&TickInput {
+            direction: (0.0, 0.0),
+            commands,
+        });
+        while game_state.textbox.advance() {} // Show the answer at once
+    }
+
+    fn last_line(game_state: &GameState) -> String {
+        line_text(game_state.textbox_view().lines.last().unwrap())
+    }
+
+    #[test]
+    fn same_inputs_give_the_same_state() {
+        let mut first = new_game();
+        let mut second = new_game();
+
+        for game_state in [&mut first, &mut second] {
+            walk(game_state, (-1.0, 0.0), 20);
+            walk(game_state, (0.0, -1.0), 7);
+            walk(game_state, (1.0, 1.0), 13);
+        }
+
+        assert_eq!(first.character_position.x, second.character_position.x);
+        assert_eq!(first.character_position.y, second.character_position.y);
+    }
+
+    #[test]
+    fn half_a_second_of_ticks_covers_half_the_speed() {
+        let mut game_state = new_game();
+        let start_y = game_state.character_position.y;
+
+        walk(&mut game_state, (0.0, -1.0), TICKS_PER_SECOND as usize / 2);
+
+        let expected = CHARACTER_SPEED * game_state.character_speed_multiplier / 2.0;
+        assert!((start_y - game_state.character_position.y - expected).abs() < 0.01);
+    }
+
+    #[test]
+    fn confirm_turns_the_pages_of_the_welcome_text() {
+        let mut game_state = new_game();
+
+        game_state.update(&confirm()); // Reveal the rest of the first page at once
+        assert!(game_state.textbox.is_waiting());
+        walk(&mut game_state, (0.0, 0.0), TICKS_PER_SECOND as usize);
+        assert!(game_state.textbox.is_waiting()); // Still waiting for the player
+
+        game_state.update(&confirm());
+        walk(&mut game_state, (0.0, 0.0), TICKS_PER_SECOND as usize * 10);
+        assert!(game_state.textbox.is_finished());
+    }
+
+    #[test]
+    fn talking_in_the_shop_starts_a_conversation_and_stops_walking() {
+        let mut game_state = new_game();
+        while game_state.textbox.advance() {} // Skip the welcome text
+
+        game_state.update(&confirm());
+        assert!(game_state.conversation.is_none()); // Nobody to talk to at the start
+
+        game_state.character_position = CharacterPosition::new(16, 8); // Inside the Sheep Shop
+        game_state.update(&confirm());
+        assert!(game_state.conversation.is_some());
+        game_state.update(&confirm()); // Reveal the keeper's greeting
+        let view: Vec<String> = game_state.textbox_view().lines.iter().map(|line| line_text(line)).collect();
+        assert!(view.iter().any(|line| line.starts_with("Sheep: ")));
+        assert_eq!(view.last().unwrap(), "  Goodbye.");
+
+        walk(&mut game_state, (-1.0, 0.0), 10);
+        assert_eq!(game_state.character_cell(), (16, 8));
+    }
+
+    #[test]
+    fn the_open_history_pauses_the_game_and_lists_every_message() {
+        let mut game_state = new_game();
+        let toggle_history = TickInput {
+            direction: (0.0, 0.0),
+            commands: vec![Command::ToggleHistory],
+        };
+        assert!(game_state.history_view().is_none());
+
+        game_state.update(&toggle_history);
+        walk(&mut game_state, (-1.0, 0.0), 10);
+        game_state.update(&confirm()); // Ignored while the history is open
+        assert_eq!(game_state.character_cell(), (7, 8));
+        assert!(!game_state.textbox.is_waiting());
+        let history: Vec<String> = game_state.history_view().unwrap().lines.iter().map(|line| line_text(line)).collect();
+        assert!(history[0].starts_with("00:00 \"Welcome"));
+        assert_eq!(history.last().unwrap(), "00:00 .");
+
+        game_state.update(&toggle_history);
+        assert!(game_state.history_view().is_none());
+        walk(&mut game_state, (-1.0, 0.0), 10);
+        assert_ne!(game_state.character_cell(), (7, 8));
+    }
+
+    #[test]
+    fn typed_commands_are_echoed_and_answered() {
+        let mut game_state = new_game();
+        while game_state.textbox.advance() {} // Skip the welcome text
+
+        type_command(&mut game_state, "look");
+        let view: Vec<String> = game_state.textbox_view().lines.iter().map(|line| line_text(line)).collect();
+        assert!(view.contains(&"> look".to_string()));
+        assert!(view.iter().any(|line| line.starts_with("Start. ")));
+        assert!(last_line(&game_state).ends_with("Exits: south, east."));
+
+        type_command(&mut game_state, "go west");
+        assert_eq!(last_line(&game_state), "You can't go west from here.");
+        type_command(&mut game_state, "dance");
+        assert_eq!(last_line(&game_state), "I don't know how to \"dance\".");
+        assert_eq!(game_state.textbox_view().input_line, Some(String::new())); // Ready for the next command
+    }
+
+    #[test]
+    fn going_somewhere_walks_into_the_next_room() {
+        let mut game_state = new_game();
+        while game_state.textbox.advance() {} // Skip the welcome text
+
+        type_command(&mut game_state, "s");
+        walk(&mut game_state, (0.0, 0.0), TICKS_PER_SECOND as usize * 2);
+
+        assert_eq!(game_state.world.current_room_name(), "meadow");
+        assert!(game_state.walk_direction.is_none());
+    }
+
+    #[test]
+    fn the_action_key_uses_the_object_in_front_of_the_character() {
+        let mut game_state = new_game();
+        while game_state.textbox.advance() {} // Skip the welcome text
+
+        game_state.character_position = CharacterPosition::new(24, 6); // Between the shop and the tree
+        walk(&mut game_state, (1.0, 0.0), 1); // Face the tree
+        game_state.update(&confirm());
+        game_state.update(&confirm()); // Reveal the answer
+        assert_eq!(last_line(&game_state), "rustles high in the leaves.");
+
+        type_command(&mut game_state, "look at the shop");
+        assert!(last_line(&game_state).contains("door stands open."));
+        type_command(&mut game_state, "take signpost");
+        assert_eq!(last_line(&game_state), "You can't take the signpost.");
+        type_command(&mut game_state, "use signpost");
+        assert_eq!(last_line(&game_state), "The signpost is out of reach.");
+    }
+
+    #[test]
+    fn items_are_picked_up_browsed_and_dropped() {
+        let mut game_state = new_game();
+        while game_state.textbox.advance() {} // Skip the welcome text
+        game_state.character_position = CharacterPosition::new(34, 8); // Past the tree
+        type_command(&mut game_state, "go east");
+        walk(&mut game_state, (0.0, 0.0), TICKS_PER_SECOND as usize * 3);
+        assert_eq!(game_state.world.current_room_name(), "forest");
+
+        game_state.character_position = CharacterPosition::new(10, 4); // Standing on the coins
+        game_state.update(&confirm());
+        while game_state.textbox.advance() {}
+        assert_eq!(last_line(&game_state), "You pick up 5 coins.");
+        assert_eq!((game_state.inventory.coins, game_state.inventory.stacks.len()), (5, 0)); // Into the wallet
+        type_command(&mut game_state, "take coins");
+        assert_eq!(last_line(&game_state), "You see no coins here.");
+
+        game_state.character_position = CharacterPosition::new(18, 4); // Standing on the shears
+        type_command(&mut game_state, "take shears");
+        assert_eq!(last_line(&game_state), "You pick up Shears x1.");
+
+        let press = |command| TickInput {
+            direction: (0.0, 0.0),
+            commands: vec![command],
+        };
+        game_state.update(&press(Command::CloseInput));
+        game_state.update(&press(Command::ToggleInventory));
+        let inventory: Vec<String> = game_state.inventory_view().unwrap().lines.iter().map(|line| line_text(line)).collect();
+        assert!(inventory[0].ends_with("Coins: 5"));
+        assert!(inventory[2].starts_with("> Shears") && inventory[2].ends_with("x1"));
+        walk(&mut game_state, (1.0, 0.0), 10);
+        assert_eq!(game_state.character_cell(), (18, 4)); // Paused while browsing
+
+        game_state.update(&press(Command::Drop));
+        assert!(game_state.inventory_view().is_none());
+        assert_eq!(game_state.inventory.count("shears"), 0);
+        let dropped = game_state.world.current_room().object_called("shears").unwrap();
+        assert_eq!(dropped.cells, HashSet::from([(20, 10)]));
+    }
+
+    #[test]
+    fn objects_behind_another_within_reach_can_be_taken() {
+        let mut game_state = new_game();
+        while game_state.textbox.advance() {} // Skip the welcome text
+        let cell = game_state.character_cell();
+        let object = |name: &str, action| RoomObject {
+            glyph: '*',
+            name: name.to_string(),
+            description: String::new(),
+            action,
+            cells: HashSet::from([cell]),
+        };
+        let objects = &mut game_state.world.current_room_mut().objects;
+        objects.insert(0, object("Stone", ObjectAction::Describe)); // Found first
+        objects.push(object("Coins", ObjectAction::Coins(3)));
+
+        type_command(&mut game_state, "take coins");
+        assert_eq!(last_line(&game_state), "You pick up 3 coins.");
+        assert_eq!(game_state.inventory.coins, 3);
+    }
+
+    #[test]
+    fn the_named_object_within_reach_is_taken_over_another_of_the_same_name() {
+        let mut game_state = new_game();
+        while game_state.textbox.advance() {} // Skip the welcome text
+        let cell = game_state.character_cell();
+        let wool = |quantity, cells| RoomObject {
+            glyph: '%',
+            name: "Wool".to_string(),
+            description: String::new(),
+            action: ObjectAction::Item {
+                item: "wool".to_string(),
+                quantity,
+            },
+            cells,
+        };
+        let objects = &mut game_state.world.current_room_mut().objects;
+        objects.insert(0, wool(1, HashSet::from([(0, 0)]))); // Far away, and found first by name
+        objects.push(wool(2, HashSet::from([cell])));
+
+        type_command(&mut game_state, "take wool");
+        assert_eq!(game_state.inventory.count("wool"), 2);
+        type_command(&mut game_state, "take wool");
+        assert_eq!(last_line(&game_state), "The wool is out of reach.");
+    }
+
+    #[test]
+    fn the_sheep_shop_sells_for_coins_and_buys_back() {
+        let mut game_state = new_game();
+        while game_state.textbox.advance() {} // Skip the welcome text
+        game_state.inventory.coins = 5;
+        game_state.character_position = CharacterPosition::new(16, 8); // Inside the Sheep Shop
+        let press = |commands: Vec<Command>| TickInput {
+            direction: (0.0, 0.0),
+            commands,
+        };
+
+        game_state.update(&confirm());
+        while game_state.textbox.advance() {}
+        game_state.update(&press(vec![Command::SelectNext, Command::SelectNext, Command::Confirm])); // Let's trade.
+        while game_state.textbox.advance() {}
+        assert!(game_state.conversation.is_none());
+        let menu: Vec<String> = game_state.textbox_view().lines.iter().map(|line| line_text(line)).collect();
+        assert!(menu.iter().any(|line| line.starts_with("Sheep Shop, buying") && line.ends_with("Coins: 5")));
+        assert!(menu.iter().any(|line| line.starts_with("> Wool x10") && line.ends_with("3c")));
+
+        game_state.update(&confirm());
+        while game_state.textbox.advance() {}
+        assert_eq!(game_state.message_log.entries.last().unwrap().message, "You buy Wool for 3 coins.");
+        game_state.update(&confirm());
+        while game_state.textbox.advance() {}
+        assert_eq!(game_state.message_log.entries.last().unwrap().message, "You can't afford that.");
+        assert_eq!((game_state.inventory.coins, game_state.inventory.count("wool")), (2, 1));
+        walk(&mut game_state, (-1.0, 0.0), 10);
+        assert_eq!(game_state.character_cell(), (16, 8)); // Standing still while trading
+
+        // Sell the wool back, then leave
+        game_state.update(&press(vec![Command::SelectNext, Command::SelectNext, Command::Confirm]));
+        game_state.update(&confirm());
+        while game_state.textbox.advance() {}
+        assert_eq!((game_state.inventory.coins, game_state.inventory.count("wool")), (4, 0));
+        assert_eq!(game_state.shops["sheep_shop"].stock[0].quantity, 10);
+        game_state.update(&press(vec![Command::SelectPrevious, Command::Confirm]));
+        while game_state.textbox.advance() {}
+        assert!(game_state.trade.is_none());
+        assert_eq!(last_line(&game_state), "Baa! Come again.");
+    }
+
+    #[test]
+    fn clicks_walk_the_character_and_take_choices() {
+        let mut game_state = new_game();
+        while game_state.textbox.advance() {} // Skip the welcome text
+        let click = |col: i32, row: i32| TickInput {
+            direction: (0.0, 0.0),
+            commands: vec![Command::Click(col, row)],
+        };
+
+        // The character walks until it stands on the clicked cell, feet first
+        game_state.update(&click(4, 13));
+        walk(&mut game_state, (0.0, 0.0), 60);
+        assert_eq!(game_state.character_cell(), (2, 7));
+        assert!(game_state.walk_target.is_none());
+        game_state.update(&click(9, 14));
+        walk(&mut game_state, (0.0, 1.0), 1); // Held keys take over
+        assert!(game_state.walk_target.is_none());
+
+        // Clicking a choice in the textbox takes it
+        game_state.character_position = CharacterPosition::new(16, 8); // Inside the Sheep Shop
+        game_state.update(&confirm());
+        while game_state.textbox.advance() {}
+        let row_of = |game_state: &GameState, text: &str| {
+            let view = game_state.textbox_view();
+            let row = view.lines.iter().position(|line| line_text(line).starts_with(text)).unwrap();
+            TEXTBOX_FRAME.top as i32 + 1 + row as i32
+        };
+        game_state.update(&click(5, row_of(&game_state, "  Let's trade.")));
+        while game_state.textbox.advance() {}
+        assert!(game_state.trade.is_some());
+        game_state.update(&click(5, row_of(&game_state, "Sheep Shop, buying"))); // The title is no entry
+        assert!(game_state.trade.is_some());
+        game_state.update(&click(5, row_of(&game_state, "  Leave")));
+        while game_state.textbox.advance() {}
+        assert!(game_state.trade.is_none());
+        assert_eq!(last_line(&game_state), "Baa! Come again.");
+    }
+
+    #[test]
+    fn the_pointer_finds_the_object_under_it() {
+        let mut game_state = new_game();
+        let room = game_state.world.current_room();
+        let (object_name, cell) = room
+            .objects
+            .iter()
+            .find_map(|object| {
+                let cell = object.cells.iter().find(|(col, row)| room.revealed_positions.contains(&(*row as usize, *col as usize)))?;
+                Some((object.name.clone(), *cell))
+            })
+            .unwrap();
+
+        game_state.pointer_cell = Some(cell);
+        assert_eq!(game_state.hovered_object().unwrap().name, object_name);
+        game_state.is_inventory_open = true;
+        assert!(game_state.hovered_object().is_none()); // Covered by the inventory screen
+        game_state.is_inventory_open = false;
+        game_state.pointer_cell = Some((cell.0, TEXTBOX_FRAME.top as i32 + 2));
+        assert!(game_state.hovered_object().is_none());
+    }
+}
@@ -0,0 +1,107 @@
+use std::collections::HashMap; // Import HashMap collection
+
+use sdl2::pixels::{Color, PixelFormatEnum}; // Import SDL2 color and pixel format handling
+use sdl2::rect::Rect; // Import SDL2 rectangle handling
+use sdl2::render::{BlendMode, Canvas, RenderTarget, Texture, TextureCreator}; // Import SDL2 rendering types
+use sdl2::surface::Surface; // Import SDL2 surface handling
+use sdl2::ttf::Font; // Import SDL2 TTF font handling
+
+const ATLAS_SIZE: u32 = 512; // Width and height of the atlas texture in pixels
+const SLOT_SIZE: u32 = 16; // Width and height of the atlas slot reserved for one glyph
+
+/// A single texture holding every (character, color) pair rendered so far.
+///
+/// Glyphs are rasterized from the font the first time they are drawn and
+/// copied into the next free slot; after that, drawing a glyph is a plain
+/// sub-rect copy from the shared texture, which SDL batches into very few
+/// draw calls.
+pub struct GlyphAtlas<'a> {
+    font: &'a Font<'a, 'a>,
+    texture: Texture<'a>,
+    glyphs: HashMap<(char, Color), Option<Rect>>, // Source rect of each glyph, None if the font can't render it
+    next_slot: u32,
+}
+
+impl<'a> GlyphAtlas<'a> {
+    /// Create an empty atlas texture for the given font
+    pub fn new<T>(texture_creator: &'a TextureCreator<T>, font: &'a Font<'a, 'a>) -> Result<Self, String> {
+        let mut texture = texture_creator
+            .create_texture_static(PixelFormatEnum::ARGB8888, ATLAS_SIZE, ATLAS_SIZE)
+            .map_err(|error| error.to_string())?;
+        texture.set_blend_mode(BlendMode::Blend); // Glyphs are drawn with their alpha channel
+
+        // Static textures start with undefined contents, so clear it to transparent
+        let blank = vec![0u8; (ATLAS_SIZE * ATLAS_SIZE * 4) as usize];
+        texture
+            .update(None, &blank, (ATLAS_SIZE * 4) as usize)
+            .map_err(|error| error.to_string())?;
+
+        Ok(GlyphAtlas {
+            font,
+            texture,
+            glyphs: HashMap::new(),
+            next_slot: 0,
+        })
+    }
+
+    /// Draw a character in the given color, stretched to fill `dest_rect`
+    pub fn draw_char<T: RenderTarget>(
+        &mut self,
+        canvas: &mut Canvas<T>,
+        char_to_render: char,
+        color: Color,
+        dest_rect: Rect,
+    ) -> Result<(), String> {
+        if char_to_render == ' ' {
+            return Ok(()); // Spaces are fully transparent, nothing to copy
+        }
+        if let Some(src_rect) = self.glyph(char_to_render, color)? {
+            canvas.copy(&self.texture, src_rect, dest_rect)?;
+        }
+        Ok(())
+    }
+
+    // Function to look up a glyph, rasterizing it into the atlas on first use
+    fn glyph(&mut self, char_to_render: char, color: Color) -> Result<Option<Rect>, String> {
+        if let Some(src_rect) = self.glyphs.get(&(char_to_render, color)) {
+            return Ok(*src_rect);
+        }
+
+        let src_rect = match self.font.render_char(char_to_render).blended(color) {
+            Ok(rendered_char) => self.insert(&rendered_char)?,
+            Err(_) => None, // Remember missing glyphs so they are not retried every frame
+        };
+        self.glyphs.insert((char_to_render, color), src_rect);
+        Ok(src_rect)
+    }
+
+    // Function to copy a rendered glyph into the next free slot of the atlas
+    fn insert(&mut self, rendered_char: &Surface) -> Result<Option<Rect>, String> {
+        if rendered_char.width() == 0 || rendered_char.height() == 0 {
+            return Ok(None);
+        }
+
+        let slots_per_row = ATLAS_SIZE / SLOT_SIZE;
+        if self.next_slot >= slots_per_row * slots_per_row {
+            // The atlas is full: forget everything and re-rasterize glyphs as they are needed
+            self.glyphs.clear();
+            self.next_slot = 0;
+        }
+
+        let converted = rendered_char.convert_format(PixelFormatEnum::ARGB8888)?;
+        let src_rect = Rect::new(
+            ((self.next_slot % slots_per_row) * SLOT_SIZE) as i32, // Slot x position
+            ((self.next_slot / slots_per_row) * SLOT_SIZE) as i32, // Slot y position
+            converted.width().min(SLOT_SIZE), // Clip oversized glyphs to the slot
+            converted.height().min(SLOT_SIZE),
+        );
+        let pitch = converted.pitch() as usize;
+        let texture = &mut self.texture;
+        converted
+            .with_lock(|pixels| texture.update(src_rect, pixels, pitch))
+            .map_err(|error| error.to_string())?;
+
+        self.next_slot += 1;
+        Ok(Some(src_rect))
+    }
+}
@@ -0,0 +1,240 @@
+use std::fmt; // Import formatting traits
+use std::fs; // Import file system access
+use std::path::Path; // Import path handling
+
+use sdl2::pixels::{Color, PixelFormatEnum}; // Import SDL2 color and pixel format handling
+use sdl2::render::Canvas; // Import SDL2 canvases
+use sdl2::surface::Surface; // Import SDL2 surface handling
+use sdl2::ttf::{Font, Sdl2TtfContext}; // Import SDL2 TTF font handling
+
+use crate::game::{Command, GameState, TickInput}; // Import the game simulation
+use crate::glyph_atlas::GlyphAtlas; // Import the glyph atlas
+use crate::render::render_frame; // Import the renderers
+use crate::{BASE_HEIGHT, BASE_WIDTH, FONT_PATH, FONT_SIZE, MAPS_DIR, START_ROOM}; // Import shared constants
+
+/// One step of an input script
+#[derive(Debug, PartialEq)]
+pub enum ScriptStep {
+    Hold { direction: (f32, f32), ticks: usize }, // Hold movement keys (or none) for a number of ticks
+    Press(Command), // Press a key or click once, applied during a single tick
+    Type(String), // Type a command on the input line and submit it, during a single tick
+}
+
+/// A sequence of inputs replayed tick by tick against a game
+///
+/// Scripts are plain text with one step per line:
+///
+/// ```text
+/// # Walk to the shop and read the rest of the text
+/// hold right 30
+/// hold up+left 12
+/// wait 10
+/// press scroll_down
+/// press confirm
+/// type go east
+/// click 20 4                 <- click the cell in column 20, row 4
+/// ```
+#[derive(Debug, PartialEq)]
+pub struct InputScript {
+    pub steps: Vec<ScriptStep>,
+}
+
+/// Error reported for an unreadable or malformed input script
+#[derive(Debug)]
+pub struct ScriptError {
+    pub line: usize, // Line of the script, or 0 if the file couldn't be read
+    pub message: String,
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "line {}: {}", self.line, self.message)
+        }
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+impl InputScript {
+    /// Load and parse an input script from disk
+    pub fn load(path: &Path) -> Result<InputScript, ScriptError> {
+        let source = fs::read_to_string(path).map_err(|error| ScriptError {
+            line: 0,
+            message: error.to_string(),
+        })?;
+        InputScript::parse(&source)
+    }
+
+    /// Parse an input script
+    pub fn parse(source: &str) -> Result<InputScript, ScriptError> {
+        let mut steps = Vec::new();
+
+        for (index, line) in source.lines().enumerate() {
+            let script_error = |message: String| ScriptError {
+                line: index + 1,
+                message,
+            };
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let step = match words.as_slice() {
+                [] => continue, // Skip blank lines
+                [first, ..] if first.starts_with('#') => continue, // Skip comments
+                ["hold", keys, ticks] => ScriptStep::Hold {
+                    direction: parse_direction(keys)
+                        .ok_or_else(|| script_error(format!("unknown direction `{}`", keys)))?,
+                    ticks: parse_ticks(ticks).ok_or_else(|| script_error(format!("invalid tick count `{}`", ticks)))?,
+                },
+                ["wait", ticks] => ScriptStep::Hold {
+                    direction: (0.0, 0.0),
+                    ticks: parse_ticks(ticks).ok_or_else(|| script_error(format!("invalid tick count `{}`", ticks)))?,
+                },
+                ["press", key] => ScriptStep::Press(
+                    parse_command(key).ok_or_else(|| script_error(format!("unknown key `{}`", key)))?,
+                ),
+                ["type", ..] => ScriptStep::Type(words[1..].join(" ")),
+                ["click", col, row] => match (col.parse(), row.parse()) {
+                    (Ok(col), Ok(row)) => ScriptStep::Press(Command::Click(col, row)),
+                    _ => return Err(script_error(format!("invalid cell `{} {}`", col, row))),
+                },
+                _ => return Err(script_error(format!("unknown step `{}`", line.trim()))),
+            };
+            steps.push(step);
+        }
+
+        Ok(InputScript { steps })
+    }
+
+    /// Replay the script against a game and return the number of ticks simulated
+    pub fn run(&self, game_state: &mut GameState) -> usize {
+        let mut ticks_run = 0;
+        for step in &self.steps {
+            match step {
+                ScriptStep::Hold { direction, ticks } => {
+                    let tick_input = TickInput {
+                        direction: *direction,
+                        commands: Vec::new(),
+                    };
+                    for _ in 0..*ticks {
+                        game_state.update(&tick_input);
+                    }
+                    ticks_run += ticks;
+                }
+                ScriptStep::Press(command) => {
+                    game_state.update(&TickInput {
+                        direction: (0.0, 0.0),
+                        commands: vec![*command],
+                    });
+                    ticks_run += 1;
+                }
+                ScriptStep::Type(text) => {
+                    let mut commands = vec![Command::OpenInput];
+                    commands.extend(text.chars().map(Command::Type));
+                    commands.push(Command::Submit);
+                    game_state.update(&TickInput {
+                        direction: (0.0, 0.0),
+                        commands,
+                    });
+                    ticks_run += 1;
+                }
+            }
+        }
+        ticks_run
+    }
+}
+
+/// Prepare SDL for rendering without a display
+///
+/// Only the TTF library is initialized; the dummy video driver is selected
+/// so that nothing can open a real window by accident.
+pub fn init() -> Result<Sdl2TtfContext, String> {
+    sdl2::hint::set("SDL_VIDEODRIVER", "dummy");
+    sdl2::ttf::init().map_err(|error| error.to_string())
+}
+
+/// Render one frame of the game into an off-screen surface, `scale` times the base resolution
+pub fn render_to_surface(game_state: &GameState, font: &Font, scale: u32) -> Result<Surface<'static>, String> {
+    render_offscreen(font, scale, |glyph_atlas, canvas| {
+        render_frame(game_state, glyph_atlas, canvas, scale as f32, scale as f32);
+    })
+}
+
+/// Draw into a black off-screen surface, `scale` times the base resolution
+///
+/// `draw` gets a fresh glyph atlas and a software canvas to call any of the
+/// renderers with, which lets tests capture single parts of the screen.
+pub fn render_offscreen<F>(font: &Font, scale: u32, draw: F) -> Result<Surface<'static>, String>
+where
+    F: FnOnce(&mut GlyphAtlas, &mut Canvas<Surface<'static>>),
+{
+    let surface = Surface::new(BASE_WIDTH * scale, BASE_HEIGHT * scale, PixelFormatEnum::RGB888)?;
+    let mut canvas = surface.into_canvas()?;
+    canvas.set_draw_color(Color::BLACK);
+    canvas.clear();
+
+    let texture_creator = canvas.texture_creator();
+    {
+        let mut glyph_atlas = GlyphAtlas::new(&texture_creator, font)?;
+        draw(&mut glyph_atlas, &mut canvas);
+    }
+    drop(texture_creator); // Release the renderer before taking the surface back
+    Ok(canvas.into_surface())
+}
+
+/// Start a new game, replay a script against it and save the final frame as a BMP
+pub fn run(script_path: &Path, frame_path: &Path) -> Result<(), String> {
+    let script = InputScript::load(script_path).map_err(|error| format!("{}: {}", script_path.display(), error))?;
+    let mut game_state = GameState::load(Path::new(MAPS_DIR), START_ROOM).map_err(|error| error.to_string())?;
+    let ticks_run = script.run(&mut game_state);
+
+    let ttf_context = init()?;
+    let font = ttf_context.load_font(FONT_PATH, FONT_SIZE)?;
+    let frame = render_to_surface(&game_state, &font, 1)?;
+    frame.save_bmp(frame_path)?;
+
+    println!("Ran {} ticks, saved the final frame to {}", ticks_run, frame_path.display());
+    Ok(())
+}
+
+// Function to parse held keys such as `left`, `up+right` or `none`
+fn parse_direction(keys: &str) -> Option<(f32, f32)> {
+    let mut direction = (0.0, 0.0);
+    for key in keys.split('+') {
+        match key {
+            "left" => direction.0 -= 1.0,
+            "right" => direction.0 += 1.0,
+            "up" => direction.1 -= 1.0,
+            "down" => direction.1 += 1.0,
+            "none" => {}
+            _ => return None,
+        }
+    }
+    Some(direction)
+}
+
+// Function to parse a tick count
+fn parse_ticks(ticks: &str) -> Option<usize> {
+    ticks.parse().ok()
+}
+
+// Function to parse the name of a one-shot key press
+fn parse_command(key: &str) -> Option<Command> {
+    match key {
+        "scroll_up" => Some(Command::ScrollUp),
+        "scroll_down" => Some(Command::ScrollDown),
+        "confirm" => Some(Command::Confirm),
+        "select_previous" => Some(Command::SelectPrevious),
+        "select_next" => Some(Command::SelectNext),
+        "toggle_history" => Some(Command::ToggleHistory),
+        "open_input" => Some(Command::OpenInput),
+        "erase" => Some(Command::Erase),
+        "submit" => Some(Command::Submit),
+        "close_input" => Some(Command::CloseInput),
+        "toggle_inventory" => Some(Command::ToggleInventory),
+        "drop" => Some(Command::Drop),
+        "toggle_save_menu" => Some(Command::ToggleSaveMenu),
+        "toggle_load_menu" => Some(Command::ToggleLoadMenu),
+        _ => None,
+    }
+}
@@ -0,0 +1,715 @@
+use std::collections::{HashMap, HashSet}; // Import HashMap and HashSet collections
+use std::fs; // Import file system access
+use std::io; // Import I/O error kinds
+use std::path::Path; // Import path handling
+
+use sdl2::controller::{Axis, Button, GameController}; // Import SDL2 game controller handling
+use sdl2::event::{Event, WindowEvent}; // Import SDL2 event handling
+use sdl2::keyboard::Keycode; // Import SDL2 keycode handling
+use sdl2::mouse::{MouseButton, MouseWheelDirection}; // Import SDL2 mouse handling
+use sdl2::GameControllerSubsystem; // Import the SDL2 game controller subsystem
+
+use crate::game::Command; // Import the game's commands
+use crate::map::MapError; // Import data file errors
+use crate::render::grid_cell_at; // Import the screen grid layout
+
+/// How far the stick must tilt before it walks, as a fraction of a full tilt
+pub const DEFAULT_STICK_DEADZONE: f32 = 0.25;
+// Sine of 22.5 degrees: a stick axis only walks if the stick points less
+// than that far from it, so the stick walks in one of eight directions
+const STICK_SNAP: f32 = 0.382_683_43;
+
+/// Something the player can do with a key or a controller button
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum InputAction {
+    MoveLeft, // Walk left while held
+    MoveRight, // Walk right while held
+    MoveUp, // Walk up while held
+    MoveDown, // Walk down while held
+    ScrollUp,
+    ScrollDown,
+    Confirm,
+    SelectPrevious,
+    SelectNext,
+    ToggleHistory,
+    ToggleInventory,
+    Drop,
+    OpenInput,
+    ToggleSaveMenu,
+    ToggleLoadMenu,
+    ToggleFullscreen,
+    CycleWindowSize,
+    Screenshot, // Save the frame as a BMP
+    TextDump, // Save the frame as a text grid
+    Quit,
+}
+
+/// Every action, in the order they are listed in
+pub const ACTIONS: [InputAction; 20] = [
+    InputAction::MoveLeft,
+    InputAction::MoveRight,
+    InputAction::MoveUp,
+    InputAction::MoveDown,
+    InputAction::ScrollUp,
+    InputAction::ScrollDown,
+    InputAction::Confirm,
+    InputAction::SelectPrevious,
+    InputAction::SelectNext,
+    InputAction::ToggleHistory,
+    InputAction::ToggleInventory,
+    InputAction::Drop,
+    InputAction::OpenInput,
+    InputAction::ToggleSaveMenu,
+    InputAction::ToggleLoadMenu,
+    InputAction::ToggleFullscreen,
+    InputAction::CycleWindowSize,
+    InputAction::Screenshot,
+    InputAction::TextDump,
+    InputAction::Quit,
+];
+
+// Actions allowed to share a key or button: walking up or down also picks the choice above or below
+const SHARED_KEYS: [(InputAction, InputAction); 2] = [
+    (InputAction::MoveUp, InputAction::SelectPrevious),
+    (InputAction::MoveDown, InputAction::SelectNext),
+];
+
+// Names of the keys without a character of their own, as written in the key bindings file
+const KEY_NAMES: [(&str, Keycode); 26] = [
+    ("Left", Keycode::Left),
+    ("Right", Keycode::Right),
+    ("Up", Keycode::Up),
+    ("Down", Keycode::Down),
+    ("Return", Keycode::Return),
+    ("Keypad Enter", Keycode::KpEnter),
+    ("Escape", Keycode::Escape),
+    ("Space", Keycode::Space),
+    ("Tab", Keycode::Tab),
+    ("Backspace", Keycode::Backspace),
+    ("Page Up", Keycode::PageUp),
+    ("Page Down", Keycode::PageDown),
+    ("Home", Keycode::Home),
+    ("End", Keycode::End),
+    ("F1", Keycode::F1),
+    ("F2", Keycode::F2),
+    ("F3", Keycode::F3),
+    ("F4", Keycode::F4),
+    ("F5", Keycode::F5),
+    ("F6", Keycode::F6),
+    ("F7", Keycode::F7),
+    ("F8", Keycode::F8),
+    ("F9", Keycode::F9),
+    ("F10", Keycode::F10),
+    ("F11", Keycode::F11),
+    ("F12", Keycode::F12),
+];
+
+// Names of the game controller buttons, as written after `Pad` in the key bindings file
+const BUTTON_NAMES: [(&str, Button); 15] = [
+    ("A", Button::A),
+    ("B", Button::B),
+    ("X", Button::X),
+    ("Y", Button::Y),
+    ("Back", Button::Back),
+    ("Guide", Button::Guide),
+    ("Start", Button::Start),
+    ("Left Stick", Button::LeftStick),
+    ("Right Stick", Button::RightStick),
+    ("Left Shoulder", Button::LeftShoulder),
+    ("Right Shoulder", Button::RightShoulder),
+    ("Up", Button::DPadUp),
+    ("Down", Button::DPadDown),
+    ("Left", Button::DPadLeft),
+    ("Right", Button::DPadRight),
+];
+
+/// A key or game controller button an action is bound to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Binding {
+    Key(Keycode),
+    Button(Button), // On any controller plugged in
+}
+
+/// The keys and controller buttons bound to every action, several per
+/// action if the player likes, and how the controller's stick walks
+///
+/// The defaults can be changed in a key bindings file, where every line
+/// binds an action to the keys and buttons listed, replacing its defaults:
+///
+/// ```text
+/// # Walk with WASD as well as the arrow keys
+/// move_left = Left, A, Pad Left
+/// move_right = Right, D, Pad Right
+/// drop = X, Pad B            <- D walks right now, so drop with X
+/// screenshot =               <- no keys at all
+/// stick_deadzone = 0.3       <- tilt the stick further before walking
+/// ```
+///
+/// Keys are written as the character they type (in any letter case) or by
+/// name, like `Page Up`, `Keypad Enter` or `F5`, and controller buttons as
+/// `Pad` and the button, like `Pad A`, `Pad Start` or `Pad Up` on the D-pad.
+/// A key or button may only be bound to one action, except that walking up
+/// or down can share its bindings with selecting the previous or next
+/// choice. The left stick walks as well as the movement bindings, once
+/// tilted past the deadzone.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyBindings {
+    bindings: Vec<(InputAction, Vec<Binding>)>, // In the order of `ACTIONS`
+    pub stick_deadzone: f32, // Fraction of a full tilt, from 0 up to but not including 1
+}
+
+/// What the mouse did, on the screen grid
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MouseInput {
+    Point(Option<(i32, i32)>), // The pointer moved onto a cell (column, row), or out of the window
+    Click(i32, i32), // The left button was pressed on a cell
+    Scroll(i32), // The wheel turned this many notches, positive away from the player
+}
+
+/// The input layer: turns SDL events into the actions bound to them and
+/// keeps track of the movement keys, buttons and sticks held, opening game
+/// controllers as they are plugged in, and finds the cells of the screen
+/// grid the mouse points at
+pub struct Input {
+    pub key_bindings: KeyBindings,
+    pub scale: (f32, f32), // Scaling factors of the window, to find the cell under the mouse
+    pub controller_subsystem: Option<GameControllerSubsystem>, // Opens controllers plugged in, if set
+    controllers: Vec<GameController>, // Kept open to receive their events
+    held_keys: HashSet<Keycode>,
+    held_buttons: HashSet<(u32, Button)>, // With the controller holding each one
+    sticks: HashMap<u32, (f32, f32)>, // Left stick tilt of every controller, each axis -1 to 1
+}
+
+impl InputAction {
+    /// The name of the action in the key bindings file
+    pub fn name(self) -> &'static str {
+        match self {
+            InputAction::MoveLeft => "move_left",
+            InputAction::MoveRight => "move_right",
+            InputAction::MoveUp => "move_up",
+            InputAction::MoveDown => "move_down",
+            InputAction::ScrollUp => "scroll_up",
+            InputAction::ScrollDown => "scroll_down",
+            InputAction::Confirm => "confirm",
+            InputAction::SelectPrevious => "select_previous",
+            InputAction::SelectNext => "select_next",
+            InputAction::ToggleHistory => "toggle_history",
+            InputAction::ToggleInventory => "toggle_inventory",
+            InputAction::Drop => "drop",
+            InputAction::OpenInput => "open_input",
+            InputAction::ToggleSaveMenu => "toggle_save_menu",
+            InputAction::ToggleLoadMenu => "toggle_load_menu",
+            InputAction::ToggleFullscreen => "toggle_fullscreen",
+            InputAction::CycleWindowSize => "cycle_window_size",
+            InputAction::Screenshot => "screenshot",
+            InputAction::TextDump => "text_dump",
+            InputAction::Quit => "quit",
+        }
+    }
+
+    /// Whether the action walks while its key is held, rather than happening once per press
+    pub fn is_movement(self) -> bool {
+        matches!(self, InputAction::MoveLeft | InputAction::MoveRight | InputAction::MoveUp | InputAction::MoveDown)
+    }
+
+    /// The command the action gives the game, if it is one for the game rather than for the window
+    pub fn command(self) -> Option<Command> {
+        match self {
+            InputAction::ScrollUp => Some(Command::ScrollUp),
+            InputAction::ScrollDown => Some(Command::ScrollDown),
+            InputAction::Confirm => Some(Command::Confirm),
+            InputAction::SelectPrevious => Some(Command::SelectPrevious),
+            InputAction::SelectNext => Some(Command::SelectNext),
+            InputAction::ToggleHistory => Some(Command::ToggleHistory),
+            InputAction::ToggleInventory => Some(Command::ToggleInventory),
+            InputAction::Drop => Some(Command::Drop),
+            InputAction::OpenInput => Some(Command::OpenInput),
+            InputAction::ToggleSaveMenu => Some(Command::ToggleSaveMenu),
+            InputAction::ToggleLoadMenu => Some(Command::ToggleLoadMenu),
+            _ => None,
+        }
+    }
+}
+
+impl KeyBindings {
+    /// The keys and buttons the game is played with out of the box
+    pub fn defaults() -> KeyBindings {
+        use Binding::{Button as Pad, Key};
+        let bindings = ACTIONS
+            .iter()
+            .map(|&action| {
+                let bindings = match action {
+                    InputAction::MoveLeft => vec![Key(Keycode::Left), Pad(Button::DPadLeft)],
+                    InputAction::MoveRight => vec![Key(Keycode::Right), Pad(Button::DPadRight)],
+                    InputAction::MoveUp | InputAction::SelectPrevious => vec![Key(Keycode::Up), Pad(Button::DPadUp)],
+                    InputAction::MoveDown | InputAction::SelectNext => vec![Key(Keycode::Down), Pad(Button::DPadDown)],
+                    InputAction::ScrollUp => vec![Key(Keycode::PageUp), Pad(Button::LeftShoulder)],
+                    InputAction::ScrollDown => vec![Key(Keycode::PageDown), Pad(Button::RightShoulder)],
+                    InputAction::Confirm => vec![Key(Keycode::Return), Key(Keycode::KpEnter), Key(Keycode::Space), Pad(Button::A)],
+                    InputAction::ToggleHistory => vec![Key(Keycode::H), Pad(Button::X)],
+                    InputAction::ToggleInventory => vec![Key(Keycode::I), Pad(Button::Y)],
+                    InputAction::Drop => vec![Key(Keycode::D), Pad(Button::B)],
+                    InputAction::OpenInput => vec![Key(Keycode::Tab)],
+                    InputAction::ToggleSaveMenu => vec![Key(Keycode::F5), Pad(Button::Start)],
+                    InputAction::ToggleLoadMenu => vec![Key(Keycode::F9), Pad(Button::Back)],
+                    InputAction::ToggleFullscreen => vec![Key(Keycode::F)],
+                    InputAction::CycleWindowSize => vec![Key(Keycode::R)],
+                    InputAction::Screenshot => vec![Key(Keycode::F12)],
+                    InputAction::TextDump => vec![Key(Keycode::F11)],
+                    InputAction::Quit => vec![Key(Keycode::Escape)],
+                };
+                (action, bindings)
+            })
+            .collect();
+        KeyBindings {
+            bindings,
+            stick_deadzone: DEFAULT_STICK_DEADZONE,
+        }
+    }
+
+    /// Load a key bindings file over the defaults, or just the defaults if there is no such file
+    pub fn load(path: &Path) -> Result<KeyBindings, MapError> {
+        match fs::read_to_string(path) {
+            Ok(source) => KeyBindings::parse(&source, path),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(KeyBindings::defaults()),
+            Err(error) => Err(MapError::Io {
+                path: path.to_path_buf(),
+                error,
+            }),
+        }
+    }
+
+    /// Parse key bindings file contents over the defaults; `path` is only used in error messages
+    pub fn parse(source: &str, path: &Path) -> Result<KeyBindings, MapError> {
+        let mut key_bindings = KeyBindings::defaults();
+        let mut binding_lines = vec![0; ACTIONS.len()]; // Line that bound each action, 0 for the defaults
+        let syntax_error = |line: usize, message: String| MapError::Syntax {
+            path: path.to_path_buf(),
+            line,
+            message,
+        };
+
+        for (index, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue; // Skip blank lines and comments
+            }
+            let (name, keys) = line
+                .split_once('=')
+                .ok_or_else(|| syntax_error(index + 1, format!("expected `action = keys`, found `{}`", line)))?;
+            if name.trim() == "stick_deadzone" {
+                key_bindings.stick_deadzone = keys
+                    .trim()
+                    .parse()
+                    .ok()
+                    .filter(|deadzone| (0.0..1.0).contains(deadzone))
+                    .ok_or_else(|| syntax_error(index + 1, format!("invalid stick deadzone `{}`", keys.trim())))?;
+                continue;
+            }
+            let action_index = ACTIONS
+                .iter()
+                .position(|action| action.name() == name.trim())
+                .ok_or_else(|| syntax_error(index + 1, format!("unknown action `{}`", name.trim())))?;
+            let keys = keys
+                .split(',')
+                .map(str::trim)
+                .filter(|key| !key.is_empty())
+                .map(|key| parse_binding(key).ok_or_else(|| syntax_error(index + 1, format!("unknown key `{}`", key))))
+                .collect::<Result<Vec<_>, _>>()?;
+            key_bindings.bindings[action_index].1 = keys;
+            binding_lines[action_index] = index + 1;
+        }
+
+        // Check that no key or button is left bound to two actions that can't share it
+        for (first_index, (first_action, first_keys)) in key_bindings.bindings.iter().enumerate() {
+            for (second_index, (second_action, second_keys)) in key_bindings.bindings.iter().enumerate().skip(first_index + 1) {
+                let may_share = SHARED_KEYS.contains(&(*first_action, *second_action));
+                if let Some(key) = first_keys.iter().find(|key| second_keys.contains(key)).filter(|_| !may_share) {
+                    return Err(syntax_error(
+                        binding_lines[first_index].max(binding_lines[second_index]),
+                        format!("`{}` is bound to both `{}` and `{}`", binding_name(*key), first_action.name(), second_action.name()),
+                    ));
+                }
+            }
+        }
+        Ok(key_bindings)
+    }
+
+    /// The keys and buttons bound to an action
+    pub fn bindings(&self, action: InputAction) -> &[Binding] {
+        let (_, bindings) = self.bindings.iter().find(|(bound, _)| *bound == action).expect("every action has bindings");
+        bindings
+    }
+
+    /// The action pressing a key or button triggers, leaving out walking, which lasts while it is held
+    pub fn pressed_action(&self, binding: Binding) -> Option<InputAction> {
+        self.bindings
+            .iter()
+            .find(|(action, bindings)| !action.is_movement() && bindings.contains(&binding))
+            .map(|(action, _)| *action)
+    }
+
+    /// Whether a key or button walks the character while it is held
+    pub fn is_movement(&self, binding: Binding) -> bool {
+        self.bindings
+            .iter()
+            .any(|(action, bindings)| action.is_movement() && bindings.contains(&binding))
+    }
+}
+
+impl Input {
+    /// An input layer with nothing held yet, which only opens controllers once given the subsystem
+    pub fn new(key_bindings: KeyBindings) -> Input {
+        Input {
+            key_bindings,
+            scale: (1.0, 1.0),
+            controller_subsystem: None,
+            controllers: Vec::new(),
+            held_keys: HashSet::new(),
+            held_buttons: HashSet::new(),
+            sticks: HashMap::new(),
+        }
+    }
+
+    /// Take in one event and return the action it triggers, if any
+    pub fn handle_event(&mut self, event: &Event) -> Option<InputAction> {
+        match event {
+            Event::KeyDown {
+                keycode: Some(keycode),
+                ..
+            } => {
+                if self.key_bindings.is_movement(Binding::Key(*keycode)) {
+                    self.held_keys.insert(*keycode);
+                }
+                self.key_bindings.pressed_action(Binding::Key(*keycode))
+            }
+            Event::KeyUp {
+                keycode: Some(keycode),
+                ..
+            } => {
+                self.held_keys.remove(keycode);
+                None
+            }
+            Event::ControllerButtonDown { which, button, .. } => {
+                if self.key_bindings.is_movement(Binding::Button(*button)) {
+                    self.held_buttons.insert((*which, *button));
+                }
+                self.key_bindings.pressed_action(Binding::Button(*button))
+            }
+            Event::ControllerButtonUp { which, button, .. } => {
+                self.held_buttons.remove(&(*which, *button));
+                None
+            }
+            Event::ControllerAxisMotion { which, axis, value, .. } => {
+                let tilt = (*value as f32 / i16::MAX as f32).max(-1.0);
+                let stick = self.sticks.entry(*which).or_insert((0.0, 0.0));
+                match axis {
+                    Axis::LeftX => stick.0 = tilt,
+                    Axis::LeftY => stick.1 = tilt,
+                    _ => {}
+                }
+                None
+            }
+            // SDL also reports the controllers already plugged in when it starts
+            Event::ControllerDeviceAdded { which, .. } => {
+                if let Some(controller_subsystem) = &self.controller_subsystem {
+                    match controller_subsystem.open(*which) {
+                        Ok(controller) => {
+                            println!("Controller connected: {}", controller.name());
+                            self.controllers.push(controller);
+                        }
+                        Err(error) => eprintln!("Failed to open controller: {}", error),
+                    }
+                }
+                None
+            }
+            Event::ControllerDeviceRemoved { which, .. } => {
+                self.controllers.retain(|controller| controller.instance_id() != *which);
+                self.held_buttons.retain(|(holder, _)| holder != which);
+                self.sticks.remove(which);
+                None
+            }
+            // Keys and buttons released while another window has the focus never report it
+            Event::Window {
+                win_event: WindowEvent::FocusLost,
+                ..
+            } => {
+                self.held_keys.clear();
+                self.held_buttons.clear();
+                self.sticks.clear();
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// Take in one event and return what it does with the mouse, if anything
+    pub fn handle_mouse_event(&self, event: &Event) -> Option<MouseInput> {
+        let (scale_x, scale_y) = self.scale;
+        match event {
+            Event::MouseMotion { x, y, .. } => Some(MouseInput::Point(Some(grid_cell_at(*x, *y, scale_x, scale_y)))),
+            Event::Window {
+                win_event: WindowEvent::Leave,
+                ..
+            } => Some(MouseInput::Point(None)),
+            Event::MouseButtonDown {
+                mouse_btn: MouseButton::Left,
+                x,
+                y,
+                ..
+            } => {
+                let (col, row) = grid_cell_at(*x, *y, scale_x, scale_y);
+                Some(MouseInput::Click(col, row))
+            }
+            Event::MouseWheel { y, direction, .. } if *y != 0 => match direction {
+                MouseWheelDirection::Flipped => Some(MouseInput::Scroll(-y)),
+                _ => Some(MouseInput::Scroll(*y)),
+            },
+            _ => None,
+        }
+    }
+
+    /// Whether a key or button bound to an action is held down
+    pub fn is_held(&self, action: InputAction) -> bool {
+        self.key_bindings.bindings(action).iter().any(|binding| match binding {
+            Binding::Key(keycode) => self.held_keys.contains(keycode),
+            Binding::Button(button) => self.held_buttons.iter().any(|(_, held)| held == button),
+        })
+    }
+
+    /// The direction of the movement keys and buttons held down, or else of
+    /// the stick tilted furthest, each axis -1, 0 or 1
+    pub fn direction(&self) -> (f32, f32) {
+        let axis = |negative: InputAction, positive: InputAction| self.is_held(positive) as i32 as f32 - self.is_held(negative) as i32 as f32;
+        let held_direction = (
+            axis(InputAction::MoveLeft, InputAction::MoveRight),
+            axis(InputAction::MoveUp, InputAction::MoveDown),
+        );
+        if held_direction != (0.0, 0.0) {
+            return held_direction;
+        }
+
+        let Some((x, y)) = self.sticks.values().copied().max_by(|a, b| a.0.hypot(a.1).total_cmp(&b.0.hypot(b.1))) else {
+            return (0.0, 0.0);
+        };
+        let tilt = x.hypot(y);
+        if tilt <= self.key_bindings.stick_deadzone {
+            return (0.0, 0.0);
+        }
+        let snap = |value: f32| if value.abs() > tilt * STICK_SNAP { value.signum() } else { 0.0 };
+        (snap(x), snap(y))
+    }
+}
+
+/// Whether a key types a character on the input line rather than doing anything else while typing
+pub fn is_text_key(keycode: Keycode) -> bool {
+    (0x20..0x7f).contains(&keycode.into_i32())
+}
+
+// Function to find the key or button named in a key bindings file: `Pad`
+// and one of `BUTTON_NAMES`, a key that types a character, or one of
+// `KEY_NAMES`, in any letter case
+fn parse_binding(name: &str) -> Option<Binding> {
+    if let Some(button_name) = name.get(..4).filter(|prefix| prefix.eq_ignore_ascii_case("pad ")).map(|_| name[4..].trim()) {
+        return BUTTON_NAMES
+            .iter()
+            .find(|(named, _)| named.eq_ignore_ascii_case(button_name))
+            .map(|(_, button)| Binding::Button(*button));
+    }
+    let mut chars = name.chars();
+    let keycode = match (chars.next(), chars.next()) {
+        (Some(char_typed), None) if char_typed.is_ascii_graphic() => Keycode::from_i32(char_typed.to_ascii_lowercase() as i32),
+        _ => KEY_NAMES
+            .iter()
+            .find(|(key_name, _)| key_name.eq_ignore_ascii_case(name))
+            .map(|(_, keycode)| *keycode),
+    };
+    keycode.map(Binding::Key)
+}
+
+// Function to name a key or button the way the key bindings file does
+fn binding_name(binding: Binding) -> String {
+    match binding {
+        Binding::Button(button) => match BUTTON_NAMES.iter().find(|(_, named)| *named == button) {
+            Some((name, _)) => format!("Pad {}", name),
+            None => format!("Pad {:?}", button),
+        },
+        Binding::Key(keycode) => match KEY_NAMES.iter().find(|(_, named)| *named == keycode) {
+            Some((name, _)) => name.to_string(),
+            None => char::from_u32(keycode.into_i32() as u32).map_or_else(|| "?".to_string(), |char_typed| char_typed.to_ascii_uppercase().to_string()),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sdl2::keyboard::Mod;
+    use sdl2::mouse::MouseState;
+
+    fn key_down(keycode: Keycode) -> Event {
+        Event::KeyDown {
+            timestamp: 0,
+            window_id: 0,
+            keycode: Some(keycode),
+            scancode: None,
+            keymod: Mod::NOMOD,
+            repeat: false,
+        }
+    }
+
+    fn key_up(keycode: Keycode) -> Event {
+        Event::KeyUp {
+            timestamp: 0,
+            window_id: 0,
+            keycode: Some(keycode),
+            scancode: None,
+            keymod: Mod::NOMOD,
+            repeat: false,
+        }
+    }
+
+    fn button(which: u32, button: Button, is_down: bool) -> Event {
+        if is_down {
+            Event::ControllerButtonDown { timestamp: 0, which, button }
+        } else {
+            Event::ControllerButtonUp { timestamp: 0, which, button }
+        }
+    }
+
+    fn stick(which: u32, axis: Axis, value: i16) -> Event {
+        Event::ControllerAxisMotion { timestamp: 0, which, axis, value }
+    }
+
+    fn mouse_down(x: i32, y: i32, mouse_btn: MouseButton) -> Event {
+        Event::MouseButtonDown { timestamp: 0, window_id: 0, which: 0, mouse_btn, clicks: 1, x, y }
+    }
+
+    fn wheel(y: i32, direction: MouseWheelDirection) -> Event {
+        Event::MouseWheel { timestamp: 0, window_id: 0, which: 0, x: 0, y, direction, precise_x: 0.0, precise_y: y as f32, mouse_x: 0, mouse_y: 0 }
+    }
+
+    #[test]
+    fn the_defaults_bind_several_keys_without_conflicts() {
+        let defaults = KeyBindings::defaults();
+
+        assert_eq!(KeyBindings::parse("", Path::new("keys.txt")).unwrap(), defaults);
+        assert_eq!(
+            defaults.bindings(InputAction::Confirm),
+            [Binding::Key(Keycode::Return), Binding::Key(Keycode::KpEnter), Binding::Key(Keycode::Space), Binding::Button(Button::A)]
+        );
+        assert_eq!(defaults.pressed_action(Binding::Key(Keycode::Space)), Some(InputAction::Confirm));
+        assert_eq!(defaults.pressed_action(Binding::Key(Keycode::Up)), Some(InputAction::SelectPrevious)); // And walks up while held
+        assert_eq!(defaults.pressed_action(Binding::Button(Button::Start)), Some(InputAction::ToggleSaveMenu));
+    }
+
+    #[test]
+    fn the_file_remaps_and_swaps_keys() {
+        let source = "# Left-handed\nmove_left = Left, a\nmove_right = Right, D\ndrop = x, pad b\n\
+                      toggle_history = I, Pad Y\ntoggle_inventory = h, PAD X\nscreenshot =\nquit = Keypad Enter, escape\n\
+                      confirm = Return\nstick_deadzone = 0.5\n";
+        let key_bindings = KeyBindings::parse(source, Path::new("keys.txt")).unwrap();
+
+        assert_eq!(key_bindings.bindings(InputAction::MoveLeft), [Binding::Key(Keycode::Left), Binding::Key(Keycode::A)]);
+        assert_eq!(key_bindings.pressed_action(Binding::Key(Keycode::D)), None);
+        assert!(key_bindings.is_movement(Binding::Key(Keycode::D)));
+        assert_eq!(key_bindings.pressed_action(Binding::Key(Keycode::I)), Some(InputAction::ToggleHistory));
+        assert_eq!(key_bindings.pressed_action(Binding::Button(Button::X)), Some(InputAction::ToggleInventory));
+        assert!(key_bindings.bindings(InputAction::Screenshot).is_empty());
+        assert_eq!(key_bindings.pressed_action(Binding::Key(Keycode::KpEnter)), Some(InputAction::Quit));
+        assert_eq!(key_bindings.pressed_action(Binding::Button(Button::A)), None);
+        assert_eq!(key_bindings.stick_deadzone, 0.5);
+    }
+
+    #[test]
+    fn conflicts_and_unknown_names_are_errors() {
+        let conflict = KeyBindings::parse("quit = Q\n\nmove_right = Right, D\n", Path::new("keys.txt")).unwrap_err();
+        assert_eq!(conflict.to_string(), "keys.txt:3: `D` is bound to both `move_right` and `drop`");
+        let button_conflict = KeyBindings::parse("quit = Pad Start\n", Path::new("keys.txt")).unwrap_err();
+        assert_eq!(button_conflict.to_string(), "keys.txt:1: `Pad Start` is bound to both `toggle_save_menu` and `quit`");
+
+        let unknown_key = KeyBindings::parse("quit = Pause\n", Path::new("keys.txt")).unwrap_err();
+        assert_eq!(unknown_key.to_string(), "keys.txt:1: unknown key `Pause`");
+        let unknown_action = KeyBindings::parse("jump = Space\n", Path::new("keys.txt")).unwrap_err();
+        assert_eq!(unknown_action.to_string(), "keys.txt:1: unknown action `jump`");
+        let invalid_deadzone = KeyBindings::parse("stick_deadzone = 1.5\n", Path::new("keys.txt")).unwrap_err();
+        assert_eq!(invalid_deadzone.to_string(), "keys.txt:1: invalid stick deadzone `1.5`");
+    }
+
+    #[test]
+    fn held_keys_walk_until_released() {
+        let key_bindings = KeyBindings::parse("move_up = Up, W\n", Path::new("keys.txt")).unwrap();
+        let mut input = Input::new(key_bindings);
+
+        assert_eq!(input.handle_event(&key_down(Keycode::W)), None);
+        assert_eq!(input.handle_event(&key_down(Keycode::Right)), None);
+        assert_eq!(input.direction(), (1.0, -1.0));
+        assert_eq!(input.handle_event(&key_down(Keycode::Up)), Some(InputAction::SelectPrevious));
+        input.handle_event(&key_up(Keycode::W));
+        input.handle_event(&key_up(Keycode::Right));
+        assert_eq!(input.direction(), (0.0, -1.0)); // Up is still held
+        assert_eq!(input.handle_event(&key_down(Keycode::H)), Some(InputAction::ToggleHistory));
+    }
+
+    #[test]
+    fn controller_buttons_trigger_the_same_actions() {
+        let mut input = Input::new(KeyBindings::defaults());
+
+        assert_eq!(input.handle_event(&button(0, Button::A, true)), Some(InputAction::Confirm));
+        assert_eq!(input.handle_event(&button(0, Button::DPadDown, true)), Some(InputAction::SelectNext));
+        assert_eq!(input.handle_event(&button(1, Button::DPadLeft, true)), None);
+        assert_eq!(input.direction(), (-1.0, 1.0));
+        input.handle_event(&button(0, Button::DPadDown, false));
+        assert_eq!(input.direction(), (-1.0, 0.0));
+
+        // Unplugging a controller lets go of everything it held
+        input.handle_event(&Event::ControllerDeviceRemoved { timestamp: 0, which: 1 });
+        assert_eq!(input.direction(), (0.0, 0.0));
+    }
+
+    #[test]
+    fn the_stick_walks_in_eight_directions_past_the_deadzone() {
+        let mut input = Input::new(KeyBindings::defaults());
+
+        input.handle_event(&stick(0, Axis::LeftX, 6000)); // Under a quarter tilt
+        assert_eq!(input.direction(), (0.0, 0.0));
+        input.handle_event(&stick(0, Axis::LeftX, 30000));
+        input.handle_event(&stick(0, Axis::LeftY, -8000));
+        assert_eq!(input.direction(), (1.0, 0.0)); // Closer to right than to up and right
+        input.handle_event(&stick(0, Axis::LeftY, -20000));
+        assert_eq!(input.direction(), (1.0, -1.0));
+        input.handle_event(&stick(0, Axis::RightX, i16::MIN)); // The right stick doesn't walk
+        assert_eq!(input.direction(), (1.0, -1.0));
+
+        // Held buttons win over the stick
+        input.handle_event(&button(0, Button::DPadLeft, true));
+        assert_eq!(input.direction(), (-1.0, 0.0));
+        input.handle_event(&button(0, Button::DPadLeft, false));
+        input.handle_event(&stick(0, Axis::LeftX, 0));
+        input.handle_event(&stick(0, Axis::LeftY, 0));
+        assert_eq!(input.direction(), (0.0, 0.0));
+    }
+
+    #[test]
+    fn the_mouse_points_at_cells_of_the_scaled_grid() {
+        let mut input = Input::new(KeyBindings::defaults());
+        input.scale = (3.0, 3.0); // Each cell is 24 pixels square
+
+        assert_eq!(input.handle_mouse_event(&mouse_down(23, 24, MouseButton::Left)), Some(MouseInput::Click(0, 1)));
+        assert_eq!(input.handle_mouse_event(&mouse_down(500, 70, MouseButton::Right)), None);
+        let motion = Event::MouseMotion {
+            timestamp: 0,
+            window_id: 0,
+            which: 0,
+            mousestate: MouseState::from_sdl_state(0),
+            x: 500,
+            y: 70,
+            xrel: 1,
+            yrel: 0,
+        };
+        assert_eq!(input.handle_mouse_event(&motion), Some(MouseInput::Point(Some((20, 2)))));
+        assert_eq!(input.handle_mouse_event(&wheel(2, MouseWheelDirection::Normal)), Some(MouseInput::Scroll(2)));
+        assert_eq!(input.handle_mouse_event(&wheel(2, MouseWheelDirection::Flipped)), Some(MouseInput::Scroll(-2)));
+        assert_eq!(input.handle_event(&motion), None); // Nothing for the keys and buttons
+    }
+}
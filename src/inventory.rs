@@ -0,0 +1,313 @@
+use std::collections::HashMap; // Import HashMap collection
+use std::fs; // Import file system access
+use std::path::Path; // Import path handling
+
+use sdl2::pixels::Color; // Import SDL2 color handling
+
+use crate::map::{parse_color, MapError}; // Import map file handling
+use crate::markup::{plain_line, style_lines}; // Import textbox markup
+use crate::render::wrap_text; // Import text wrapping
+use crate::textbox::TextboxView; // Import the textbox view
+
+/// Slots the player can carry stacks of items in
+pub const INVENTORY_SLOTS: usize = 8;
+/// Drawn on the landscape where coins lie
+pub const COIN_GLYPH: char = 'o';
+/// Color of coins lying on the landscape
+pub const COIN_COLOR: Color = Color::YELLOW;
+
+/// What an item is, as loaded from the items file
+#[derive(Clone, Debug, PartialEq)]
+pub struct ItemDefinition {
+    pub name: String,
+    pub description: String,
+    pub stack_limit: u32, // Most of the item one slot holds
+    pub glyph: char, // Drawn on the landscape where the item lies
+    pub color: Color,
+    pub use_text: Option<String>, // Shown when the item is used, if it can be
+}
+
+/// A quantity of one item held in a slot
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ItemStack {
+    pub item: String, // Name of the item's section in the items file
+    pub quantity: u32,
+}
+
+/// The stacks of items the player carries, at most one per slot, and the player's wallet
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Inventory {
+    pub stacks: Vec<ItemStack>,
+    pub coins: u32,
+}
+
+/// Load the item definitions of an items file
+///
+/// Every item is a section named after the item, with its properties:
+///
+/// ```text
+/// [wool]
+/// name = Wool
+/// description = A tuft of soft white wool.
+/// stack = 20                 <- most of the item one slot holds, 1 if left out
+/// glyph = %                  <- drawn where the item lies, with its color
+/// color = white
+/// use = It is very soft.     <- shown when the item is used, if it can be
+/// ```
+pub fn load_items(path: &Path) -> Result<HashMap<String, ItemDefinition>, MapError> {
+    let source = fs::read_to_string(path).map_err(|error| MapError::Io {
+        path: path.to_path_buf(),
+        error,
+    })?;
+    parse_items(&source, path)
+}
+
+/// Parse items file contents; `path` is only used in error messages
+pub fn parse_items(source: &str, path: &Path) -> Result<HashMap<String, ItemDefinition>, MapError> {
+    let mut items = HashMap::new();
+    let mut current_item: Option<(String, ItemDefinition)> = None;
+
+    for (index, line) in source.lines().enumerate() {
+        let syntax_error = |message: String| MapError::Syntax {
+            path: path.to_path_buf(),
+            line: index + 1,
+            message,
+        };
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue; // Skip blank lines and comments
+        }
+
+        if let Some(id) = line.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+            // A new item section, which finishes the previous one
+            if let Some((id, item)) = current_item.take() {
+                items.insert(id, item);
+            }
+            let id = id.trim().to_string();
+            if items.contains_key(&id) {
+                return Err(syntax_error(format!("duplicate item `{}`", id)));
+            }
+            current_item = Some((
+                id.clone(),
+                ItemDefinition {
+                    name: id,
+                    description: String::new(),
+                    stack_limit: 1,
+                    glyph: '*',
+                    color: Color::WHITE,
+                    use_text: None,
+                },
+            ));
+            continue;
+        }
+
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| syntax_error(format!("expected `key = value`, found `{}`", line)))?;
+        let (key, value) = (key.trim(), value.trim());
+        let Some((_, item)) = current_item.as_mut() else {
+            return Err(syntax_error(format!("item property `{}` before the first item", key)));
+        };
+        match key {
+            "name" => item.name = value.to_string(),
+            "description" => item.description = value.to_string(),
+            "stack" => {
+                item.stack_limit = value
+                    .parse()
+                    .ok()
+                    .filter(|stack_limit| *stack_limit > 0)
+                    .ok_or_else(|| syntax_error(format!("invalid stack limit `{}`", value)))?;
+            }
+            "glyph" => {
+                let mut glyphs = value.chars();
+                item.glyph = match (glyphs.next(), glyphs.next()) {
+                    (Some(glyph), None) => glyph,
+                    _ => return Err(syntax_error(format!("glyph must be one character, found `{}`", value))),
+                };
+            }
+            "color" => {
+                item.color = parse_color(value).ok_or_else(|| syntax_error(format!("unknown color `{}`", value)))?;
+            }
+            "use" => item.use_text = Some(value.to_string()),
+            _ => return Err(syntax_error(format!("unknown item property `{}`", key))),
+        }
+    }
+
+    if let Some((id, item)) = current_item.take() {
+        items.insert(id, item);
+    }
+    Ok(items)
+}
+
+impl Inventory {
+    /// Add `quantity` of an item, topping up its stacks before filling free
+    /// slots, and return how many didn't fit
+    pub fn add(&mut self, item: &str, quantity: u32, items: &HashMap<String, ItemDefinition>) -> u32 {
+        let stack_limit = items[item].stack_limit;
+        let mut remaining = quantity;
+        for stack in self.stacks.iter_mut().filter(|stack| stack.item == item) {
+            let added = remaining.min(stack_limit.saturating_sub(stack.quantity));
+            stack.quantity += added;
+            remaining -= added;
+        }
+        while remaining > 0 && self.stacks.len() < INVENTORY_SLOTS {
+            let added = remaining.min(stack_limit);
+            self.stacks.push(ItemStack {
+                item: item.to_string(),
+                quantity: added,
+            });
+            remaining -= added;
+        }
+        remaining
+    }
+
+    /// Take `quantity` of an item out, emptiest stacks first, if that many are carried
+    pub fn remove(&mut self, item: &str, quantity: u32) -> bool {
+        if self.count(item) < quantity {
+            return false;
+        }
+        let mut remaining = quantity;
+        while remaining > 0 {
+            let (index, stack) = self
+                .stacks
+                .iter_mut()
+                .enumerate()
+                .filter(|(_, stack)| stack.item == item)
+                .min_by_key(|(_, stack)| stack.quantity)
+                .expect("enough of the item is carried");
+            let removed = remaining.min(stack.quantity);
+            stack.quantity -= removed;
+            remaining -= removed;
+            if stack.quantity == 0 {
+                self.stacks.remove(index);
+            }
+        }
+        true
+    }
+
+    /// How much of an item is carried, over all stacks
+    pub fn count(&self, item: &str) -> u32 {
+        self.stacks.iter().filter(|stack| stack.item == item).map(|stack| stack.quantity).sum()
+    }
+
+    /// The inventory screen: the coins carried, one line per slot with the
+    /// `selected` one marked, then the description of the selected item, wrapped at `width`.
+    /// At most `visible_lines` lines are shown, scrolled just far enough to show the selected slot.
+    pub fn view(&self, selected: usize, items: &HashMap<String, ItemDefinition>, width: usize, visible_lines: usize) -> TextboxView {
+        let coins = format!("Coins: {}", self.coins);
+        let padding = width.saturating_sub("Inventory".len() + coins.len()).max(1);
+        let title = format!("{{yellow}}Inventory{{/}}{}{}", " ".repeat(padding), coins);
+        let mut lines = style_lines([title.as_str(), ""]);
+        for slot in 0..INVENTORY_SLOTS {
+            let marker = if slot == selected { '>' } else { ' ' };
+            let line = match self.stacks.get(slot) {
+                Some(stack) => {
+                    let quantity = format!("x{}", stack.quantity);
+                    let name_width = width.saturating_sub(quantity.len() + 3);
+                    let name: String = items[&stack.item].name.chars().take(name_width).collect();
+                    format!("{} {:<name_width$} {}", marker, name, quantity)
+                }
+                None => format!("{} -", marker),
+            };
+            lines.push(plain_line(&line));
+        }
+        lines.push(Vec::new());
+        if let Some(stack) = self.stacks.get(selected) {
+            let description = wrap_text(&items[&stack.item].description, width);
+            lines.extend(style_lines(description.lines()));
+        }
+
+        let total_lines = lines.len();
+        let selected_line = 2 + selected; // Below the title and the blank line after it
+        let first_line = (selected_line + 1)
+            .saturating_sub(visible_lines)
+            .min(total_lines.saturating_sub(visible_lines));
+        let end = (first_line + visible_lines).min(total_lines);
+        TextboxView {
+            lines: lines.drain(first_line..end).collect(),
+            first_line,
+            total_lines,
+            shows_continue_indicator: false,
+            input_line: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markup::line_text;
+
+    fn items() -> HashMap<String, ItemDefinition> {
+        parse_items(
+            "[wool]\nname = Wool\ndescription = Soft.\nstack = 5\n\n[shears]\nname = Shears\ndescription = Sharp.\n",
+            Path::new("items.txt"),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn items_stack_up_to_their_limit_in_free_slots() {
+        let items = items();
+        let mut inventory = Inventory::default();
+
+        assert_eq!(inventory.add("wool", 3, &items), 0);
+        assert_eq!(inventory.add("wool", 4, &items), 0);
+        assert_eq!(inventory.stacks.iter().map(|stack| stack.quantity).collect::<Vec<_>>(), vec![5, 2]);
+        assert_eq!(inventory.add("shears", 10, &items), 4); // One per slot, six slots left
+        assert_eq!(inventory.stacks.len(), INVENTORY_SLOTS);
+        assert_eq!(inventory.count("wool"), 7);
+    }
+
+    #[test]
+    fn removing_takes_from_the_emptiest_stack() {
+        let items = items();
+        let mut inventory = Inventory::default();
+        inventory.add("wool", 7, &items);
+
+        assert!(!inventory.remove("wool", 8));
+        assert!(inventory.remove("wool", 3));
+        assert_eq!(inventory.stacks, vec![ItemStack { item: "wool".to_string(), quantity: 4 }]);
+    }
+
+    #[test]
+    fn the_view_marks_the_selected_slot_and_describes_it() {
+        let items = items();
+        let mut inventory = Inventory::default();
+        inventory.add("wool", 2, &items);
+        inventory.add("shears", 1, &items);
+        inventory.coins = 12;
+
+        let lines: Vec<String> = inventory.view(1, &items, 16, 16).lines.iter().map(|line| line_text(line)).collect();
+        assert_eq!(lines[0], "Inventory Coins: 12");
+        assert_eq!(lines[2], "  Wool        x2");
+        assert_eq!(lines[3], "> Shears      x1");
+        assert_eq!(lines[4], "  -");
+        assert_eq!(lines.last().unwrap(), "Sharp.");
+    }
+
+    #[test]
+    fn the_view_scrolls_to_keep_the_selected_slot_in_the_frame() {
+        let items = items();
+        let mut inventory = Inventory::default();
+        inventory.add("shears", 6, &items);
+
+        let top = inventory.view(0, &items, 16, 4);
+        assert_eq!((top.first_line, top.total_lines), (0, INVENTORY_SLOTS + 4));
+        assert_eq!(top.lines.iter().map(|line| line_text(line)).collect::<Vec<_>>(), ["Inventory Coins: 0", "", "> Shears      x1", "  Shears      x1"]);
+
+        let scrolled = inventory.view(5, &items, 16, 4);
+        assert_eq!(scrolled.first_line, 4);
+        assert_eq!(line_text(scrolled.lines.last().unwrap()), "> Shears      x1");
+        let bottom = inventory.view(7, &items, 16, 4);
+        assert_eq!(bottom.first_line, 6);
+        assert_eq!(line_text(bottom.lines.last().unwrap()), "> -"); // The last slot, which is empty
+    }
+
+    #[test]
+    fn item_errors_name_the_line() {
+        let error = parse_items("[wool]\nstack = none\n", Path::new("items.txt")).unwrap_err();
+
+        assert_eq!(error.to_string(), "items.txt:2: invalid stack limit `none`");
+    }
+}
@@ -0,0 +1,40 @@
+extern crate sdl2; // Import the SDL2 library
+
+pub mod collision; // Walls and character footprints
+pub mod dialogue; // Branching conversations
+pub mod game; // Game state and the fixed-timestep simulation
+pub mod glyph_atlas; // Shared texture cache for rendered glyphs
+pub mod headless; // Scripted runs without a window
+pub mod input; // Key and controller bindings, and the mouse on the screen grid
+pub mod inventory; // Items and the stacks of them the player carries
+pub mod map; // Map file loading
+pub mod markup; // Inline markup for textbox text
+pub mod message_log; // History of the messages shown in the textbox
+pub mod movement; // Frame-rate independent character movement
+pub mod object; // Interactable objects marked on the landscape
+pub mod parser; // Typed commands in the style of classic text adventures
+pub mod render; // Renderers for every part of the screen
+pub mod save; // Saved games and the slots they are kept in
+pub mod screenshot; // Saving the current frame to disk
+pub mod shop; // Shops and trading with them
+pub mod textbox; // Typewriter message queue shown in the textbox
+pub mod world; // Rooms and the links between them
+
+// Constants for window and character dimensions
+pub const BASE_WIDTH: u32 = 320; // Base width for window scaling
+pub const BASE_HEIGHT: u32 = 200; // Base height for window scaling
+pub const TEXT_AREA_HEIGHT: u32 = 9; // Height of the text area in characters
+pub const TEXT_AREA_WIDTH: u32 = 35; // Width of the text area in characters
+pub const CHAR_WIDTH: u32 = 8; // Character width for rendering
+pub const CHAR_HEIGHT: u32 = 8; // Character height for rendering
+pub const CHARACTER_SPEED: f32 = 8.0; // Character movement speed in cells per second
+pub const TEXT_REVEAL_SPEED: f32 = 40.0; // Characters revealed per second in the textbox
+
+// Locations of the game data
+pub const FONT_PATH: &str = "src/PetMe64.ttf"; // Font used for every glyph
+pub const FONT_SIZE: u16 = 8; // Point size the font is loaded at
+pub const MAPS_DIR: &str = "maps"; // Directory the map files are loaded from
+pub const START_ROOM: &str = "start"; // Room the character starts in
+pub const SCREENSHOTS_DIR: &str = "screenshots"; // Directory captures of the screen are saved to
+pub const SAVES_DIR: &str = "saves"; // Directory saved games are kept in
+pub const KEY_BINDINGS_PATH: &str = "keys.txt"; // File the player can remap keys and controller buttons in
@@ -2,27 +2,48 @@ extern crate sdl2; // Import the SDL2 library
 
 use sdl2::event::Event; // Import SDL2 event handling
 use sdl2::keyboard::Keycode; // Import SDL2 keycode handling
-use sdl2::pixels::Color; // Import SDL2 color handling
-use sdl2::rect::Rect; // Import SDL2 rectangle handling
 use sdl2::ttf::Font; // Import SDL2 TTF font handling
 use sdl2::video::FullscreenType; // Import SDL2 fullscreen handling
 
+use the_fabricof::game::{Command, GameState, TickInput, TICK_DURATION}; // Import the game simulation
+use the_fabricof::glyph_atlas::GlyphAtlas; // Import the glyph atlas
+use the_fabricof::headless; // Import the headless runner
+use the_fabricof::map::MapError; // Import map loading errors
+use the_fabricof::input::{is_text_key, Input, InputAction, KeyBindings, MouseInput}; // Import key bindings and mouse input
+use the_fabricof::render::render_frame; // Import the renderers
+use the_fabricof::screenshot::{self, Capture}; // Import screen captures
+use the_fabricof::{BASE_HEIGHT, BASE_WIDTH, FONT_PATH, FONT_SIZE, KEY_BINDINGS_PATH, MAPS_DIR, SCREENSHOTS_DIR, START_ROOM}; // Import shared constants
+
 // Import standard libraries
 use std::time::Duration; // Import duration handling
-use std::collections::HashSet; // Import HashSet collection
+use std::path::Path; // Import path handling
 
-// Constants for window and character dimensions
-const BASE_WIDTH: u32 = 320; // Base width for window scaling
-const BASE_HEIGHT: u32 = 200; // Base height for window scaling
-const TEXT_AREA_HEIGHT: u32 = 9; // Height of the text area in characters
-const TEXT_AREA_WIDTH: u32 = 35; // Width of the text area in characters
-const CHAR_WIDTH: u32 = 8; // Character width for rendering
-const CHAR_HEIGHT: u32 = 8; // Character height for rendering
-const CHARACTER_SPEED: f32 = 8.0; // Character movement speed in pixels per second
+const MAX_FRAME_TIME: f32 = 0.25; // Longest frame time simulated, so a stall doesn't cause a burst of ticks
 
 fn main() {
+    // Run a scripted game without a window: --headless <script> <frame.bmp>
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(index) = args.iter().position(|arg| arg == "--headless") {
+        let (Some(script_path), Some(frame_path)) = (args.get(index + 1), args.get(index + 2)) else {
+            eprintln!("Usage: {} --headless <script> <frame.bmp>", args[0]);
+            std::process::exit(2);
+        };
+        if let Err(error) = headless::run(Path::new(script_path), Path::new(frame_path)) {
+            eprintln!("Headless run failed: {}", error);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // Load the keys the player has remapped, if any, before opening a window
+    let key_bindings = KeyBindings::load(Path::new(KEY_BINDINGS_PATH)).unwrap_or_else(|error| {
+        eprintln!("Failed to load key bindings: {}", error);
+        std::process::exit(1);
+    });
+
     // Initialize SDL2 context and subsystems
     let (sdl_context, video_subsystem, ttf_context) = initialize_sdl2();
+    video_subsystem.text_input().start(); // Receive typed text for the input line
 
     // Get the current display mode to determine screen dimensions
     let (screen_width, screen_height) = get_screen_dimensions(&video_subsystem);
@@ -49,448 +70,184 @@ fn main() {
         .unwrap();
     //println!("main() 2 window width: {}, Window height: {}", window_width, window_height);
 
-    // Initialize fullscreen state and canvas
+    // Initialize fullscreen state and canvas, synchronized to the display refresh with --vsync
+    let is_vsync = args.iter().any(|arg| arg == "--vsync");
     let mut is_fullscreen = false;
-    let mut canvas = window.into_canvas().build().unwrap();
+    let mut canvas_builder = window.into_canvas();
+    if is_vsync {
+        canvas_builder = canvas_builder.present_vsync();
+    }
+    let mut canvas = canvas_builder.build().unwrap();
     
     // Load the font for rendering text
-    let font_path = "src/PetMe64.ttf";
-    let font_size = 8;
     let font: Font = ttf_context
-        .load_font(font_path, font_size)
+        .load_font(FONT_PATH, FONT_SIZE)
         .expect("Failed to load font");
 
-    // Load pictures for background, landscape, and character
-    let background_picture = get_background_picture();
-    let landscape_picture = get_landscape_picture();
-    let character_picture = get_character_picture();
-
-    // Initialize character position
-    let mut character_x: i32 = 7;
-    let character_y: i32 = 8;
-
-    // Initialize character speed multiplier
-    #[allow(unused_mut)]
-    let mut character_speed_multiplier: f32 = 1.5;
-
-    // Initialize textbox text as a mutable vector of strings
-    #[allow(unused_mut)]
-    let mut textbox_texts: Vec<String> = vec![
-        String::from("\"Welcome   to The Fabricof! Welcome to The Fabricof! Welcome to The Fabricof! Welcome to The Fabricof!"),
-        String::from("       "),
-        String::from("This is the very first text line of the game! This is the very first text line of the game!"),
-        String::from("Please, do not give up, it will be more, soon! Please, do not give up, it will be more, soon!"),
-        String::from("And this line is for the bablabla, blablablab and ablablabla!"),
-        String::from("."),
-    ];
-
-    // Initialize scroll position
-    let mut scroll_position: usize = 0;
-
-    // Initialize event pump and running state
+    // Create the glyph atlas shared by all grid renderers
+    let texture_creator = canvas.texture_creator();
+    let mut glyph_atlas = GlyphAtlas::new(&texture_creator, &font)
+        .expect("Failed to create glyph atlas");
+
+    // Load the rooms of the world and the character map, and start the game
+    let mut game_state = map_or_exit(GameState::load(Path::new(MAPS_DIR), START_ROOM));
+
+    // Carry on from the last autosave unless asked for a new game with --new-game, and keep autosaving
+    if !args.iter().any(|arg| arg == "--new-game") {
+        game_state.resume_autosave();
+    }
+    game_state.is_autosaving = true;
+
+    // Initialize event pump and timing
     let mut event_pump = sdl_context.event_pump().unwrap();
-    let mut is_running = true;
-    let mut revealed_positions: HashSet<(usize, usize)> = HashSet::new();
+    let mut input = Input::new(key_bindings);
+    // Open game controllers as they are plugged in, or play with the keyboard and mouse alone
+    match sdl_context.game_controller() {
+        Ok(controller_subsystem) => input.controller_subsystem = Some(controller_subsystem),
+        Err(error) => eprintln!("Game controllers are unavailable: {}", error),
+    }
+    let mut tick_input = TickInput::default();
+    let mut accumulator: f32 = 0.0; // Real time not yet simulated, in seconds
     let mut last_update = std::time::Instant::now();
 
     // Calculate initial scaling factors
     let (mut scale_x, mut scale_y) = calculate_scaling_factors(&canvas);
+    input.scale = (scale_x, scale_y);
 
     // Main game loop
-    while is_running {
+    while game_state.is_running {
         let now = std::time::Instant::now();
-        let delta_time = now.duration_since(last_update).as_secs_f32();
+        accumulator += now.duration_since(last_update).as_secs_f32().min(MAX_FRAME_TIME);
         last_update = now;
 
-        // Calculate the maximum scroll position
-        let max_scroll_position = textbox_texts.iter()
-            .map(|text| wrap_text(&text, TEXT_AREA_WIDTH as usize).lines().count())
-            .sum::<usize>()
-            .saturating_sub(TEXT_AREA_HEIGHT as usize);
-
         // Handle user input and events
-        let window_size_changed = handle_events(
-            &mut is_running,
+        let (window_size_changed, captures) = handle_events(
+            &mut game_state,
             &mut is_fullscreen,
             &mut current_size_index,
             &window_sizes,
             &mut canvas,
-            &mut character_x,
             &mut event_pump,
-            delta_time,
-            character_speed_multiplier,
-            &mut scroll_position,
-            max_scroll_position,
+            &mut input,
+            &mut tick_input.commands,
         );
+        tick_input.direction = input.direction();
+
+        // Run as many fixed ticks as the elapsed time covers
+        while accumulator >= TICK_DURATION {
+            game_state.update(&tick_input);
+            tick_input.commands.clear(); // Key presses only apply to the first tick
+            accumulator -= TICK_DURATION;
+        }
 
         // Recalculate scaling factors if window size changed
         if window_size_changed {
             let (new_scale_x, new_scale_y) = calculate_scaling_factors(&canvas);
             scale_x = new_scale_x;
             scale_y = new_scale_y;
+            input.scale = (scale_x, scale_y);
         }
 
-        // Clear the canvas with a black background
-        canvas.set_draw_color(Color::BLACK);
-        canvas.clear();
-
-        // Render the background and landscape
-        render_background_and_landscape(
-            &background_picture,
-            &landscape_picture,
-            &font,
-            &mut canvas,
-            scale_x,
-            scale_y,
-            (character_x as usize, character_y as usize),
-            &mut revealed_positions,
-        );
-
-        // Render the character
-        render_character(
-            &character_picture,
-            &font,
-            &mut canvas,
-            scale_x,
-            scale_y,
-            (character_x as isize, character_y as isize),
-        );
-
-        // Render the textbox
-        render_textbox(
-            &textbox_texts,
-            &font,
-            &mut canvas,
-            scale_x,
-            scale_y,
-            scroll_position,
-        );
-
-        // Present the updated canvas
-        canvas.present();
-        std::thread::sleep(Duration::from_millis(16)); // Sleep to control frame rate
-    }
-}
-
-// Function to get the background picture as a vector of strings
-fn get_background_picture() -> Vec<String> {
-    vec![
-        "########################################".to_string(),
-        "#                                      #".to_string(),
-        "#                                      #".to_string(),
-        "#                                      #".to_string(),
-        "#                                      #".to_string(),
-        "#                                      #".to_string(),
-        "#                                      #".to_string(),
-        "                                        ".to_string(),
-        "                                        ".to_string(),
-        "                                        ".to_string(),
-        "                                        ".to_string(),
-        "                                        ".to_string(),
-        "                                        ".to_string(),
-        "                                        ".to_string(),
-        "                                        ".to_string(),
-        "########################################".to_string(),
-        "#                                      #".to_string(),
-        "#                                      #".to_string(),
-        "#                                      #".to_string(),
-        "#                                      #".to_string(),
-        "#                                      #".to_string(),
-        "#                                      #".to_string(),
-        "#                                      #".to_string(),
-        "#                                      #".to_string(),
-        "########################################".to_string(),
-    ]
-}
-
-// Function to get the landscape picture as a vector of strings
-fn get_landscape_picture() -> Vec<String> {
-    vec![
-        "                                        ".to_string(),
-        "                ▒▒                      ".to_string(),
-        "                ▒▒▒▒          ♠♠♠       ".to_string(),
-        "               ▒▒▒▒▒▒▒▒▒     ♠♠♠♠♠      ".to_string(),
-        "               ▒ Sheep ▒     ♠tree♠ ♠   ".to_string(),
-        "               ▒ Shop  ▒      ♠♠♠ ♠♠♠♠  ".to_string(),
-        "               ▒▒▒▒▒▒▒▒▒       │ ♠╱♠♠♠  ".to_string(),
-        "               ▒▒▒▒▒▒▒▒▒       │ ╱ ♠♠   ".to_string(),
-        "               ▒    ▒▒▒▒     ♠♠│╱       ".to_string(),
-        "               ▒    ▒  ▒     ♠Y│        ".to_string(),
-        "               ▒    ▒  ▒      ││        ".to_string(),
-        " START         ▒    ▒▒▒▒      ╰│        ".to_string(),
-        "   │           ▒    ▒▒▒▒       │        ".to_string(),
-        "   │           ▒    ▒▒▒▒       │        ".to_string(),
-        "   │           ▒    ▒▒▒▒       │        ".to_string(),
-    ]
-}
-
-// Function to get the character picture as a vector of strings
-fn get_character_picture() -> Vec<String> {
-    vec![
-        " ╭#╮ ".to_string(),
-        " ○ ○ ".to_string(),
-        " │u│ ".to_string(),
-        " ╰▅╯ ".to_string(),
-        " ╭▒╮ ".to_string(),
-        " ▗▒▖ ".to_string(),
-        " @ @ ".to_string(),
-    ]
-}
-
-// Function to render the background and landscape
-fn render_background_and_landscape(
-    background_picture: &[String],
-    landscape_picture: &[String],
-    font: &Font,
-    canvas: &mut sdl2::render::Canvas<sdl2::video::Window>,
-    scale_x: f32,
-    scale_y: f32,
-    character_position: (usize, usize),
-    revealed_positions: &mut HashSet<(usize, usize)>,
-) {
-    // Render the background grid
-    render_grid(background_picture, font, canvas, scale_x, scale_y, Color::YELLOW);
-    // Render the landscape
-    render_landscape(
-        landscape_picture,
-        font,
-        canvas,
-        scale_x,
-        scale_y,
-        character_position,
-        revealed_positions,
-    );
-}
-
-// Function to render a grid of characters
-fn render_grid(
-    grid: &[String],
-    font: &Font,
-    canvas: &mut sdl2::render::Canvas<sdl2::video::Window>,
-    scale_x: f32,
-    scale_y: f32,
-    color: Color,
-) {
-    for (row, line) in grid.iter().enumerate() { // Iterate over each row
-        for (col, char_to_render) in line.chars().enumerate() { // Iterate over each character in the row
-            if let Ok(rendered_char) = font.render_char(char_to_render).blended(color) { // Render the character
-                let texture_creator = canvas.texture_creator(); // Create a texture creator
-                let texture = texture_creator.create_texture_from_surface(&rendered_char).unwrap(); // Create a texture from the rendered character
-
-                let dest_rect = Rect::new(
-                    (col as f32 * CHAR_WIDTH as f32 * scale_x) as i32, // Calculate x position
-                    (row as f32 * CHAR_HEIGHT as f32 * scale_y) as i32, // Calculate y position
-                    (CHAR_WIDTH as f32 * scale_x) as u32, // Calculate width
-                    (CHAR_HEIGHT as f32 * scale_y) as u32, // Calculate height
-                );
-                canvas.copy(&texture, None, dest_rect).unwrap(); // Copy the texture to the canvas
-            }
+        // Render and present the current state
+        render_frame(&game_state, &mut glyph_atlas, &mut canvas, scale_x, scale_y);
+        for capture in captures {
+            save_capture(capture, &game_state, &canvas);
         }
-    }
-}
-
-// Function to render the landscape with revealed positions
-fn render_landscape(
-    landscape_picture: &[String],
-    font: &Font,
-    canvas: &mut sdl2::render::Canvas<sdl2::video::Window>,
-    scale_x: f32,
-    scale_y: f32,
-    character_position: (usize, usize),
-    revealed_positions: &mut HashSet<(usize, usize)>,
-) {
-    let (character_x, character_y) = character_position; // Get character position
-    let reveal_radius = 6; // Set reveal radius
-
-    for (row, line) in landscape_picture.iter().enumerate() { // Iterate over each row
-        for (col, char_to_render) in line.chars().enumerate() { // Iterate over each character in the row
-            if (row as isize - character_y as isize).abs() <= reveal_radius && (col as isize - character_x as isize).abs() <= reveal_radius {
-                revealed_positions.insert((row, col)); // Reveal position if within radius
-            }
-
-            if revealed_positions.contains(&(row, col)) { // Check if position is revealed
-                if let Ok(rendered_char) = font.render_char(char_to_render).blended(Color::GREEN) { // Render the character
-                    let texture_creator = canvas.texture_creator(); // Create a texture creator
-                    let texture = texture_creator.create_texture_from_surface(&rendered_char).unwrap(); // Create a texture from the rendered character
-
-                    let dest_rect = Rect::new(
-                        (col as f32 * CHAR_WIDTH as f32 * scale_x) as i32, // Calculate x position
-                        (row as f32 * CHAR_HEIGHT as f32 * scale_y) as i32, // Calculate y position
-                        (CHAR_WIDTH as f32 * scale_x) as u32, // Calculate width
-                        (CHAR_HEIGHT as f32 * scale_y) as u32, // Calculate height
-                    );
-                    canvas.copy(&texture, None, dest_rect).unwrap(); // Copy the texture to the canvas
-                }
-            }
-        }
-    }
-}
-
-// Function to render the character
-fn render_character(
-    character_picture: &[String],
-    font: &Font,
-    canvas: &mut sdl2::render::Canvas<sdl2::video::Window>,
-    scale_x: f32,
-    scale_y: f32,
-    character_position: (isize, isize),
-) {
-    let (character_x, character_y) = character_position; // Get character position
-
-    for (row, line) in character_picture.iter().enumerate() { // Iterate over each row
-        for (col, char_to_render) in line.chars().enumerate() { // Iterate over each character in the row
-            if let Ok(rendered_char) = font.render_char(char_to_render).blended(Color::RED) { // Render the character
-                let texture_creator = canvas.texture_creator(); // Create a texture creator
-                let texture = texture_creator.create_texture_from_surface(&rendered_char).unwrap(); // Create a texture from the rendered character
-
-                let dest_x = character_x.checked_add(col as isize).expect("Overflow occurred") as f32 * CHAR_WIDTH as f32 * scale_x; // Calculate x position
-                let dest_y = character_y.checked_add(row as isize).expect("Overflow occurred") as f32 * CHAR_HEIGHT as f32 * scale_y; // Calculate y position
-
-                let dest_rect = Rect::new(
-                    dest_x as i32, // Set x position
-                    dest_y as i32, // Set y position
-                    (CHAR_WIDTH as f32 * scale_x) as u32, // Set width
-                    (CHAR_HEIGHT as f32 * scale_y) as u32, // Set height
-                );
-                //println!("Character X position{}", dest_x);
-                canvas.copy(&texture, None, dest_rect).unwrap(); // Copy the texture to the canvas
-            }
-        }
-    }
-}
-
-fn wrap_text(text: &str, max_width: usize) -> String {
-    let mut wrapped_text = String::new();
-    let mut line_length = 0;
-
-    for word in text.split(' ') {
-        if line_length + word.len() > max_width {
-            wrapped_text.push('\n');
-            line_length = 0;
-        }
-        if line_length > 0 {
-            wrapped_text.push(' ');
-            line_length += 1;
-        }
-        wrapped_text.push_str(word);
-        line_length += word.len();
-    }
-
-    wrapped_text
-}
-
-// Function to render the textbox
-fn render_textbox(
-    textbox_texts: &[String],
-    font: &Font,
-    canvas: &mut sdl2::render::Canvas<sdl2::video::Window>,
-    scale_x: f32,
-    scale_y: f32,
-    scroll_position: usize,
-) {
-    let textbox_y = BASE_HEIGHT - CHAR_HEIGHT * TEXT_AREA_HEIGHT; // Position the textbox at the bottom
-    let start_col = 2; // Start column after the frame
-    let mut current_y = textbox_y; // Initialize current y position
-
-    let mut lines: Vec<String> = Vec::new();
-    for text in textbox_texts.iter() {
-        let wrapped_text = wrap_text(&text, TEXT_AREA_WIDTH as usize);
-        for line in wrapped_text.lines() {
-            lines.push(line.to_string());
-        }
-    }
+        canvas.present();
 
-    let visible_lines = (TEXT_AREA_HEIGHT - 1) as usize; // Reduce visible lines by one to account for the frame
-    let start_line = scroll_position;
-    let end_line = (scroll_position + visible_lines).min(lines.len());
-
-    for line in &lines[start_line..end_line] {
-        for (col, char_to_render) in line.chars().enumerate() {
-            if let Ok(rendered_char) = font.render_char(char_to_render).blended(Color::WHITE) {
-                let texture_creator = canvas.texture_creator();
-                let texture = texture_creator.create_texture_from_surface(&rendered_char).unwrap();
-
-                let dest_rect = Rect::new(
-                    ((start_col + col as u32) as f32 * CHAR_WIDTH as f32 * scale_x) as i32,
-                    (current_y as f32 * scale_y) as i32,
-                    (CHAR_WIDTH as f32 * scale_x) as u32,
-                    (CHAR_HEIGHT as f32 * scale_y) as u32,
-                );
-                canvas.copy(&texture, None, dest_rect).unwrap();
-            }
+        // Without vsync, wait for the next tick instead of rendering the same state again
+        if !is_vsync {
+            std::thread::sleep(Duration::from_secs_f32((TICK_DURATION - accumulator).max(0.0)));
         }
-        current_y += CHAR_HEIGHT; // Move to the next line
     }
 }
 
-// Function to handle user input and events
+// Function to handle user input and events, returning whether the window
+// size changed and the screen captures requested
+#[allow(clippy::too_many_arguments)]
 fn handle_events(
-    is_running: &mut bool,
+    game_state: &mut GameState,
     is_fullscreen: &mut bool,
     current_size_index: &mut usize,
     window_sizes: &[(u32, u32)],
     canvas: &mut sdl2::render::Canvas<sdl2::video::Window>,
-    character_x: &mut i32,
     event_pump: &mut sdl2::EventPump,
-    delta_time: f32,
-    character_speed_multiplier: f32,
-    scroll_position: &mut usize,
-    max_scroll_position: usize,
-) -> bool {
+    input: &mut Input,
+    commands: &mut Vec<Command>,
+) -> (bool, Vec<Capture>) {
     let mut window_size_changed = false;
+    let mut captures = Vec::new();
+    let mut is_typing = game_state.input_line.is_some(); // Keys that type text go to the input line
 
     for event in event_pump.poll_iter() { // Iterate over events
         match event {
-            Event::Quit { .. } // Handle quit event
-            | Event::KeyDown {
-                keycode: Some(Keycode::Escape),
-                ..
-            } => {
-                *is_running = false; // Set running to false to exit loop
-            }
-            Event::KeyDown {
-                keycode: Some(Keycode::F),
-                ..
-            } => {
-                toggle_fullscreen(is_fullscreen, canvas); // Toggle fullscreen mode
-                window_size_changed = true;
-            }
+            Event::TextInput { text, .. } if is_typing => commands.extend(text.chars().map(Command::Type)), // Type on the input line
             Event::KeyDown {
-                keycode: Some(Keycode::R),
+                keycode: Some(Keycode::Escape),
                 ..
-            } => {
-                resize_window(is_fullscreen, current_size_index, window_sizes, canvas); // Resize window
-                window_size_changed = true;
+            } if is_typing => {
+                commands.push(Command::CloseInput); // Stop typing
+                is_typing = false;
             }
             Event::KeyDown {
-                keycode: Some(Keycode::Left),
+                keycode: Some(Keycode::Return | Keycode::KpEnter),
                 ..
-            } => *character_x -= (CHARACTER_SPEED * character_speed_multiplier * delta_time) as i32, // Move character left
+            } if is_typing => commands.push(Command::Submit), // Carry out the typed command
             Event::KeyDown {
-                keycode: Some(Keycode::Right),
+                keycode: Some(Keycode::Backspace),
                 ..
-            } => *character_x += (CHARACTER_SPEED * character_speed_multiplier * delta_time) as i32, // Move character right
+            } if is_typing => commands.push(Command::Erase), // Erase the last typed character
             Event::KeyDown {
-                keycode: Some(Keycode::Up),
+                keycode: Some(keycode),
                 ..
-            } => {
-                if *scroll_position > 0 {
-                    *scroll_position -= 1; // Scroll up
+            } if is_typing && is_text_key(keycode) => {} // Typed as text instead
+            Event::Quit { .. } => game_state.is_running = false, // Set running to false to exit loop
+            event => {
+                match input.handle_mouse_event(&event) {
+                    Some(MouseInput::Point(cell)) => game_state.pointer_cell = cell, // Highlight what the mouse points at
+                    Some(MouseInput::Click(col, row)) => commands.push(Command::Click(col, row)), // Walk there, or take the choice
+                    Some(MouseInput::Scroll(notches)) => {
+                        let command = if notches > 0 { Command::ScrollUp } else { Command::ScrollDown };
+                        commands.extend(std::iter::repeat_n(command, notches.unsigned_abs() as usize)); // Scroll a line per notch
+                    }
+                    None => {}
                 }
-            }
-            Event::KeyDown {
-                keycode: Some(Keycode::Down),
-                ..
-            } => {
-                if *scroll_position < max_scroll_position {
-                    *scroll_position += 1; // Scroll down
+                match input.handle_event(&event) {
+                    Some(InputAction::Quit) => game_state.is_running = false, // Set running to false to exit loop
+                    Some(InputAction::ToggleFullscreen) => {
+                        toggle_fullscreen(is_fullscreen, canvas); // Toggle fullscreen mode
+                        window_size_changed = true;
+                    }
+                    Some(InputAction::CycleWindowSize) => {
+                        resize_window(is_fullscreen, current_size_index, window_sizes, canvas); // Resize window
+                        window_size_changed = true;
+                    }
+                    Some(InputAction::Screenshot) => captures.push(Capture::Bitmap), // Save a screenshot
+                    Some(InputAction::TextDump) => captures.push(Capture::TextGrid), // Dump the screen as text
+                    Some(action) => {
+                        is_typing |= action == InputAction::OpenInput; // Start typing a command
+                        commands.extend(action.command()); // Hand the rest to the game
+                    }
+                    None => {}
                 }
             }
-            _ => {}
         }
     }
 
-    window_size_changed
+    (window_size_changed, captures)
+}
+
+// Function to save a capture of the frame just rendered, reporting where it went
+fn save_capture(capture: Capture, game_state: &GameState, canvas: &sdl2::render::Canvas<sdl2::video::Window>) {
+    let dir = Path::new(SCREENSHOTS_DIR);
+    let result = match capture {
+        Capture::Bitmap => screenshot::save_bitmap(canvas, dir),
+        Capture::TextGrid => screenshot::save_text_grid(game_state, dir),
+    };
+    match result {
+        Ok(path) => println!("Saved {}", path.display()),
+        Err(error) => eprintln!("Failed to save screen capture: {}", error),
+    }
 }
 
 // Function to toggle fullscreen mode
@@ -559,3 +316,11 @@ fn get_screen_dimensions(video_subsystem: &sdl2::VideoSubsystem) -> (u32, u32) {
     let display_mode = video_subsystem.current_display_mode(0).unwrap();
     (display_mode.w as u32, display_mode.h as u32)
 }
+
+/// Unwrap the result of loading maps, exiting with a readable error on failure
+fn map_or_exit<T>(result: Result<T, MapError>) -> T {
+    result.unwrap_or_else(|error| {
+        eprintln!("Failed to load map: {}", error);
+        std::process::exit(1);
+    })
+}
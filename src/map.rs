@@ -0,0 +1,261 @@
+use std::collections::HashMap; // Import HashMap collection
+use std::fmt; // Import formatting traits
+use std::fs; // Import file system access
+use std::io; // Import IO error handling
+use std::path::{Path, PathBuf}; // Import path handling
+
+use sdl2::pixels::Color; // Import SDL2 color handling
+
+/// One named grid of characters in a map file, drawn in a single color
+pub struct Layer {
+    pub color: Color,
+    pub width: usize,
+    pub height: usize,
+    pub rows: Vec<String>,
+}
+
+/// A map loaded from a text file: free-form metadata plus named layers
+///
+/// The file format is plain UTF-8:
+///
+/// ```text
+/// # Comments start with '#'
+/// name = Start              <- metadata, before the first layer
+///
+/// [landscape]               <- starts a layer
+/// color = green             <- color name or #rrggbb
+/// size = 40x15              <- width x height in characters
+/// |  START       ▒▒▒▒     |  <- one row per line, between '|' delimiters
+/// ```
+///
+/// The '|' delimiters keep leading and trailing spaces intact; every row
+/// must be exactly as wide as the layer and every layer exactly as tall.
+pub struct GameMap {
+    pub path: PathBuf,
+    pub metadata: HashMap<String, String>,
+    pub layers: HashMap<String, Layer>,
+}
+
+/// Errors reported while loading a map file
+#[derive(Debug)]
+pub enum MapError {
+    Io { path: PathBuf, error: io::Error },
+    Syntax { path: PathBuf, line: usize, message: String },
+    RaggedRow { path: PathBuf, line: usize, layer: String, expected: usize, found: usize },
+    RowCount { path: PathBuf, layer: String, expected: usize, found: usize },
+    MissingLayer { path: PathBuf, layer: String },
+    UnknownRoom { path: PathBuf, room: String },
+    InvalidMetadata { path: PathBuf, key: String, value: String },
+    UnknownDialogue { path: PathBuf, dialogue: String },
+    UnknownObject { path: PathBuf, glyph: char },
+    UnknownItem { path: PathBuf, item: String },
+    UnknownShop { path: PathBuf, shop: String },
+}
+
+impl fmt::Display for MapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MapError::Io { path, error } => write!(f, "{}: {}", path.display(), error),
+            MapError::Syntax { path, line, message } => write!(f, "{}:{}: {}", path.display(), line, message),
+            MapError::RaggedRow { path, line, layer, expected, found } => write!(
+                f,
+                "{}:{}: row in layer `{}` is {} characters wide, expected {}",
+                path.display(), line, layer, found, expected
+            ),
+            MapError::RowCount { path, layer, expected, found } => write!(
+                f,
+                "{}: layer `{}` has {} rows, expected {}",
+                path.display(), layer, found, expected
+            ),
+            MapError::MissingLayer { path, layer } => write!(f, "{}: missing layer `{}`", path.display(), layer),
+            MapError::UnknownRoom { path, room } => write!(f, "{}: unknown room `{}`", path.display(), room),
+            MapError::InvalidMetadata { path, key, value } => {
+                write!(f, "{}: invalid value `{}` for `{}`", path.display(), value, key)
+            }
+            MapError::UnknownDialogue { path, dialogue } => {
+                write!(f, "{}: unknown dialogue `{}`", path.display(), dialogue)
+            }
+            MapError::UnknownObject { path, glyph } => write!(
+                f,
+                "{}: objects layer marks `{}`, but no `object {}` key describes it",
+                path.display(), glyph, glyph
+            ),
+            MapError::UnknownItem { path, item } => write!(f, "{}: unknown item `{}`", path.display(), item),
+            MapError::UnknownShop { path, shop } => write!(f, "{}: unknown shop `{}`", path.display(), shop),
+        }
+    }
+}
+
+impl std::error::Error for MapError {}
+
+impl GameMap {
+    /// Load and parse a map file from disk
+    pub fn load(path: &Path) -> Result<GameMap, MapError> {
+        let source = fs::read_to_string(path).map_err(|error| MapError::Io {
+            path: path.to_path_buf(),
+            error,
+        })?;
+        GameMap::parse(&source, path)
+    }
+
+    /// Parse map file contents; `path` is only used in error messages
+    pub fn parse(source: &str, path: &Path) -> Result<GameMap, MapError> {
+        let mut game_map = GameMap {
+            path: path.to_path_buf(),
+            metadata: HashMap::new(),
+            layers: HashMap::new(),
+        };
+        let mut current_layer: Option<(String, Layer)> = None;
+
+        for (index, line) in source.lines().enumerate() {
+            let line_number = index + 1;
+            let syntax_error = |message: String| MapError::Syntax {
+                path: path.to_path_buf(),
+                line: line_number,
+                message,
+            };
+            let line = line.trim_end_matches('\r');
+
+            if let Some(row) = line.strip_prefix('|') {
+                // A grid row, which must belong to a layer with a known size
+                let row = row
+                    .strip_suffix('|')
+                    .ok_or_else(|| syntax_error("row must end with '|'".to_string()))?;
+                let (name, layer) = current_layer
+                    .as_mut()
+                    .ok_or_else(|| syntax_error("row outside of a layer".to_string()))?;
+                if layer.width == 0 {
+                    return Err(syntax_error(format!("layer `{}` needs a size before its rows", name)));
+                }
+                let found = row.chars().count();
+                if found != layer.width {
+                    return Err(MapError::RaggedRow {
+                        path: path.to_path_buf(),
+                        line: line_number,
+                        layer: name.clone(),
+                        expected: layer.width,
+                        found,
+                    });
+                }
+                layer.rows.push(row.to_string());
+                continue;
+            }
+
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue; // Skip blank lines and comments
+            }
+
+            if let Some(name) = line.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+                // A new layer section, which finishes the previous one
+                if let Some((name, layer)) = current_layer.take() {
+                    game_map.finish_layer(name, layer)?;
+                }
+                let name = name.trim().to_string();
+                if game_map.layers.contains_key(&name) {
+                    return Err(syntax_error(format!("duplicate layer `{}`", name)));
+                }
+                current_layer = Some((
+                    name,
+                    Layer {
+                        color: Color::WHITE,
+                        width: 0,
+                        height: 0,
+                        rows: Vec::new(),
+                    },
+                ));
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| syntax_error(format!("expected `key = value`, found `{}`", line)))?;
+            let (key, value) = (key.trim(), value.trim());
+
+            match current_layer.as_mut() {
+                None => {
+                    game_map.metadata.insert(key.to_string(), value.to_string()); // Map-wide metadata
+                }
+                Some((_, layer)) if !layer.rows.is_empty() => {
+                    return Err(syntax_error(format!("layer property `{}` after the first row", key)));
+                }
+                Some((_, layer)) => match key {
+                    "color" => {
+                        layer.color = parse_color(value)
+                            .ok_or_else(|| syntax_error(format!("unknown color `{}`", value)))?;
+                    }
+                    "size" => {
+                        let (width, height) = parse_size(value)
+                            .ok_or_else(|| syntax_error(format!("invalid size `{}`, expected WIDTHxHEIGHT", value)))?;
+                        layer.width = width;
+                        layer.height = height;
+                    }
+                    _ => return Err(syntax_error(format!("unknown layer property `{}`", key))),
+                },
+            }
+        }
+
+        if let Some((name, layer)) = current_layer.take() {
+            game_map.finish_layer(name, layer)?;
+        }
+
+        Ok(game_map)
+    }
+
+    /// Remove a layer from the map and take ownership of it
+    pub fn take_layer(&mut self, name: &str) -> Result<Layer, MapError> {
+        self.layers.remove(name).ok_or_else(|| MapError::MissingLayer {
+            path: self.path.clone(),
+            layer: name.to_string(),
+        })
+    }
+
+    // Function to check a completed layer and add it to the map
+    fn finish_layer(&mut self, name: String, layer: Layer) -> Result<(), MapError> {
+        if layer.rows.len() != layer.height {
+            return Err(MapError::RowCount {
+                path: self.path.clone(),
+                layer: name,
+                expected: layer.height,
+                found: layer.rows.len(),
+            });
+        }
+        self.layers.insert(name, layer);
+        Ok(())
+    }
+}
+
+/// Parse a color name or a #rrggbb hex value
+pub fn parse_color(value: &str) -> Option<Color> {
+    if let Some(hex) = value.strip_prefix('#') {
+        if hex.len() != 6 {
+            return None;
+        }
+        let component = |range: std::ops::Range<usize>| u8::from_str_radix(hex.get(range)?, 16).ok();
+        return Some(Color::RGB(component(0..2)?, component(2..4)?, component(4..6)?));
+    }
+
+    match value.to_ascii_lowercase().as_str() {
+        "black" => Some(Color::BLACK),
+        "white" => Some(Color::WHITE),
+        "gray" | "grey" => Some(Color::GRAY),
+        "red" => Some(Color::RED),
+        "green" => Some(Color::GREEN),
+        "blue" => Some(Color::BLUE),
+        "magenta" => Some(Color::MAGENTA),
+        "yellow" => Some(Color::YELLOW),
+        "cyan" => Some(Color::CYAN),
+        _ => None,
+    }
+}
+
+// Function to parse a `WIDTHxHEIGHT` size
+fn parse_size(value: &str) -> Option<(usize, usize)> {
+    let (width, height) = value.split_once('x')?;
+    let width = width.trim().parse().ok()?;
+    let height = height.trim().parse().ok()?;
+    if width == 0 || height == 0 {
+        return None;
+    }
+    Some((width, height))
+}
@@ -0,0 +1,173 @@
+use sdl2::pixels::Color; // Import SDL2 color handling
+
+use crate::map::parse_color; // Import color names
+
+/// Color of text outside any color tag
+pub const TEXT_COLOR: Color = Color::WHITE;
+
+/// One character of textbox text with the markup that applies to it
+#[derive(Clone, Debug, PartialEq)]
+pub struct StyledChar {
+    pub char_in_cell: char,
+    pub color: Color,
+    pub pause: f32, // Seconds to wait before revealing this character
+    pub speed: f32, // Reveal rate multiplier
+}
+
+/// A line of textbox text, one entry per grid cell
+pub type StyledLine = Vec<StyledChar>;
+
+// Inline markup understood in textbox messages:
+//
+//   {red}Sheep{/}     color by name or #rrggbb, until the matching {/}
+//   {speed 2}fast{/}  reveal twice as fast (0.5 for half speed), until {/}
+//   {pause 500}       wait 500 milliseconds before revealing what follows
+//
+// Tags take no space on screen. Anything else between braces is ordinary text.
+enum Tag {
+    Color(Color),
+    Speed(f32),
+    Pause(f32), // In seconds
+    Close,
+}
+
+/// The length in bytes of the markup tag `text` starts with, if it starts with one
+pub fn tag_length(text: &str) -> Option<usize> {
+    parse_tag(text).map(|(_, length)| length)
+}
+
+/// The number of grid columns text takes up once its tags are left out
+pub fn display_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut rest = text;
+    while let Some(char_in_text) = rest.chars().next() {
+        match tag_length(rest) {
+            Some(length) => rest = &rest[length..],
+            None => {
+                width += 1;
+                rest = &rest[char_in_text.len_utf8()..];
+            }
+        }
+    }
+    width
+}
+
+/// Apply the markup of wrapped lines, carrying open tags over to the following lines
+pub fn style_lines<'a>(lines: impl IntoIterator<Item = &'a str>) -> Vec<StyledLine> {
+    let mut styles: Vec<Tag> = Vec::new(); // Open color and speed tags, innermost last
+    let mut pause = 0.0; // Pause waiting for the next character
+    let mut styled_lines = Vec::new();
+
+    for line in lines {
+        let mut styled_line = StyledLine::new();
+        let mut rest = line;
+        while let Some(char_in_text) = rest.chars().next() {
+            if let Some((tag, length)) = parse_tag(rest) {
+                match tag {
+                    Tag::Pause(seconds) => pause += seconds,
+                    Tag::Close => {
+                        styles.pop();
+                    }
+                    tag => styles.push(tag),
+                }
+                rest = &rest[length..];
+                continue;
+            }
+
+            let color = styles.iter().rev().find_map(|tag| match tag {
+                Tag::Color(color) => Some(*color),
+                _ => None,
+            });
+            let speed = styles.iter().rev().find_map(|tag| match tag {
+                Tag::Speed(speed) => Some(*speed),
+                _ => None,
+            });
+            styled_line.push(StyledChar {
+                char_in_cell: char_in_text,
+                color: color.unwrap_or(TEXT_COLOR),
+                pause: std::mem::take(&mut pause),
+                speed: speed.unwrap_or(1.0),
+            });
+            rest = &rest[char_in_text.len_utf8()..];
+        }
+        styled_lines.push(styled_line);
+    }
+    styled_lines
+}
+
+/// A line of plain text in the default style, with no markup applied
+pub fn plain_line(text: &str) -> StyledLine {
+    text.chars()
+        .map(|char_in_cell| StyledChar {
+            char_in_cell,
+            color: TEXT_COLOR,
+            pause: 0.0,
+            speed: 1.0,
+        })
+        .collect()
+}
+
+/// The characters of a styled line without their styles
+pub fn line_text(line: &[StyledChar]) -> String {
+    line.iter().map(|styled_char| styled_char.char_in_cell).collect()
+}
+
+// Function to parse the tag `text` starts with, and its length in bytes
+fn parse_tag(text: &str) -> Option<(Tag, usize)> {
+    let rest = text.strip_prefix('{')?;
+    let end = rest.find(['}', '{'])?;
+    if !rest[end..].starts_with('}') {
+        return None; // Another brace opens first
+    }
+    let length = end + 2; // Both braces included
+
+    let tag = match rest[..end].split_whitespace().collect::<Vec<_>>().as_slice() {
+        ["/"] => Tag::Close,
+        ["pause", milliseconds] => Tag::Pause(milliseconds.parse::<u32>().ok()? as f32 / 1000.0),
+        ["speed", multiplier] => Tag::Speed(multiplier.parse::<f32>().ok().filter(|speed| *speed > 0.0)?),
+        [color] => Tag::Color(parse_color(color)?),
+        _ => return None,
+    };
+    Some((tag, length))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tags_take_no_columns() {
+        assert_eq!(display_width("{red}Sheep{/} Shop"), 10);
+        assert_eq!(display_width("wait{pause 500}"), 4);
+        assert_eq!(display_width("{unknown} {"), 11); // Not markup, so shown as text
+    }
+
+    #[test]
+    fn colors_apply_until_closed_and_across_lines() {
+        let lines = style_lines(["a{red}b", "c{/}d"]);
+
+        let colors: Vec<Vec<Color>> = lines
+            .iter()
+            .map(|line| line.iter().map(|styled_char| styled_char.color).collect())
+            .collect();
+        assert_eq!(colors, vec![vec![TEXT_COLOR, Color::RED], vec![Color::RED, TEXT_COLOR]]);
+        assert_eq!(line_text(&lines[1]), "cd");
+    }
+
+    #[test]
+    fn nested_tags_close_innermost_first() {
+        let line = &style_lines(["{#102030}{speed 2}a{/}b{/}c"])[0];
+
+        assert_eq!((line[0].color, line[0].speed), (Color::RGB(16, 32, 48), 2.0));
+        assert_eq!((line[1].color, line[1].speed), (Color::RGB(16, 32, 48), 1.0));
+        assert_eq!((line[2].color, line[2].speed), (TEXT_COLOR, 1.0));
+    }
+
+    #[test]
+    fn pauses_belong_to_the_next_character() {
+        let lines = style_lines(["Well...{pause 500}", "{pause 250}no."]);
+
+        assert_eq!(lines[1][0].pause, 0.75);
+        assert_eq!(lines[1][1].pause, 0.0);
+    }
+}
@@ -0,0 +1,118 @@
+use crate::game::TICKS_PER_SECOND; // Import the simulation rate
+use crate::markup::style_lines; // Import textbox markup
+use crate::render::wrap_text; // Import text wrapping
+use crate::textbox::TextboxView; // Import the textbox view
+use crate::TEXT_AREA_WIDTH; // Import layout constants
+
+const TIME_COLOR: &str = "gray"; // Markup color of the time in front of each message
+
+/// A message shown in the textbox, with the tick it was shown at
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogEntry {
+    pub tick: u64,
+    pub message: String,
+}
+
+/// Every message that has passed through the textbox, for the history overlay
+#[derive(Default)]
+pub struct MessageLog {
+    pub entries: Vec<LogEntry>,
+    scroll_back: usize, // Lines the history is scrolled back from the newest one
+}
+
+impl MessageLog {
+    /// Add a message shown at `tick`
+    pub fn record(&mut self, tick: u64, message: &str) {
+        self.entries.push(LogEntry {
+            tick,
+            message: message.to_string(),
+        });
+    }
+
+    /// Scroll one page back, towards older messages
+    pub fn page_up(&mut self, visible_lines: usize) {
+        let max_scroll_back = self.line_count().saturating_sub(visible_lines);
+        self.scroll_back = (self.scroll_back + visible_lines).min(max_scroll_back);
+    }
+
+    /// Scroll one page forward, towards the newest message
+    pub fn page_down(&mut self, visible_lines: usize) {
+        self.scroll_back = self.scroll_back.saturating_sub(visible_lines);
+    }
+
+    /// Show the newest messages again
+    pub fn scroll_to_end(&mut self) {
+        self.scroll_back = 0;
+    }
+
+    /// The history as `visible_lines` textbox lines, each message headed by the game time it was shown at
+    pub fn view(&self, visible_lines: usize) -> TextboxView {
+        let wrapped_entries = self.wrapped_entries();
+        let mut lines = style_lines(wrapped_entries.iter().flat_map(|entry| entry.split('\n')));
+
+        let total_lines = lines.len();
+        let end = total_lines - self.scroll_back.min(total_lines.saturating_sub(visible_lines));
+        let first_line = end.saturating_sub(visible_lines);
+        TextboxView {
+            lines: lines.drain(first_line..end).collect(),
+            first_line,
+            total_lines,
+            shows_continue_indicator: false,
+            input_line: None,
+        }
+    }
+
+    // Function to count the lines of the whole history
+    fn line_count(&self) -> usize {
+        self.wrapped_entries().iter().map(|entry| entry.split('\n').count()).sum()
+    }
+
+    // Function to wrap every entry behind its time stamp
+    fn wrapped_entries(&self) -> Vec<String> {
+        self.entries
+            .iter()
+            .map(|entry| {
+                let seconds = entry.tick / TICKS_PER_SECOND as u64;
+                let stamped = format!("{{{}}}{:02}:{:02}{{/}} {}", TIME_COLOR, seconds / 60, seconds % 60, entry.message);
+                wrap_text(&stamped, TEXT_AREA_WIDTH as usize)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markup::line_text;
+
+    fn texts(view: TextboxView) -> Vec<String> {
+        view.lines.iter().map(|line| line_text(line)).collect()
+    }
+
+    #[test]
+    fn messages_are_stamped_with_the_game_time() {
+        let mut log = MessageLog::default();
+        log.record(0, "Hello");
+        log.record(75 * TICKS_PER_SECOND as u64, "{red}Later{/}");
+
+        assert_eq!(texts(log.view(5)), vec!["00:00 Hello", "01:15 Later"]);
+    }
+
+    #[test]
+    fn pages_scroll_within_the_history() {
+        let mut log = MessageLog::default();
+        for number in 0..10 {
+            log.record(number, &format!("message {}", number));
+        }
+
+        assert_eq!(texts(log.view(4))[0], "00:00 message 6");
+        log.page_up(4);
+        assert_eq!(texts(log.view(4))[0], "00:00 message 2");
+        log.page_up(4);
+        assert_eq!(log.view(4).first_line, 0);
+        log.page_down(4);
+        log.page_down(4);
+        log.page_down(4);
+        assert_eq!(log.view(4).first_line, 6);
+    }
+}
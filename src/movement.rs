@@ -0,0 +1,141 @@
+use crate::collision::{CollisionMap, Footprint}; // Import the collision model
+
+/// The character's position in cells, with sub-cell precision
+///
+/// Movement accumulates fractional cells so that the walking speed does not
+/// depend on the frame rate; the position is only snapped to whole cells for
+/// rendering and collision checks.
+pub struct CharacterPosition {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl CharacterPosition {
+    /// Place the character at the top-left corner of a cell
+    pub fn new(cell_x: i32, cell_y: i32) -> CharacterPosition {
+        CharacterPosition {
+            x: cell_x as f32,
+            y: cell_y as f32,
+        }
+    }
+
+    /// The cell the character is drawn at
+    pub fn cell(&self) -> (i32, i32) {
+        (self.x.floor() as i32, self.y.floor() as i32)
+    }
+
+    /// Move in `direction` by `distance` cells, stopping at walls.
+    ///
+    /// Each axis moves separately so the character slides along walls, and
+    /// in steps of at most one cell so long frames can't tunnel through them.
+    pub fn move_by(&mut self, direction: (f32, f32), distance: f32, collision: &CollisionMap, footprint: &Footprint) {
+        let (mut direction_x, mut direction_y) = direction;
+        if direction_x != 0.0 && direction_y != 0.0 {
+            // Keep diagonal movement as fast as straight movement
+            direction_x *= std::f32::consts::FRAC_1_SQRT_2;
+            direction_y *= std::f32::consts::FRAC_1_SQRT_2;
+        }
+
+        self.move_axis(direction_x * distance, 0.0, collision, footprint);
+        self.move_axis(0.0, direction_y * distance, collision, footprint);
+    }
+
+    /// Move to a new cell after a room change, keeping the sub-cell offset on unchanged axes
+    pub fn snap_to(&mut self, cell: (i32, i32)) {
+        let (cell_x, cell_y) = self.cell();
+        if cell.0 != cell_x {
+            self.x = cell.0 as f32;
+        }
+        if cell.1 != cell_y {
+            self.y = cell.1 as f32;
+        }
+    }
+
+    // Function to move along one axis (the other amount is zero) in steps of at most one cell
+    fn move_axis(&mut self, mut remaining_x: f32, mut remaining_y: f32, collision: &CollisionMap, footprint: &Footprint) {
+        while remaining_x != 0.0 || remaining_y != 0.0 {
+            let step_x = remaining_x.clamp(-1.0, 1.0);
+            let step_y = remaining_y.clamp(-1.0, 1.0);
+            remaining_x -= step_x;
+            remaining_y -= step_y;
+
+            let from = self.cell();
+            let (x, y) = (self.x + step_x, self.y + step_y);
+            let to = (x.floor() as i32, y.floor() as i32);
+            if to != from && collision.blocks_move(footprint, from, to) {
+                return; // Stop in front of the wall
+            }
+            self.x = x;
+            self.y = y;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::Layer;
+    use sdl2::pixels::Color;
+
+    fn collision(rows: &[&str]) -> CollisionMap {
+        CollisionMap::from_collision_layer(&Layer {
+            color: Color::GREEN,
+            width: rows[0].chars().count(),
+            height: rows.len(),
+            rows: rows.iter().map(|row| row.to_string()).collect(),
+        })
+    }
+
+    fn single_cell() -> Footprint {
+        Footprint::from_sprite(&["o".to_string()])
+    }
+
+    #[test]
+    fn distance_does_not_depend_on_frame_rate() {
+        let open_field = collision(&["          "]);
+        let mut slow_frames = CharacterPosition::new(0, 0);
+        let mut fast_frames = CharacterPosition::new(0, 0);
+
+        for _ in 0..30 {
+            slow_frames.move_by((1.0, 0.0), 12.0 / 30.0, &open_field, &single_cell());
+        }
+        for _ in 0..144 {
+            fast_frames.move_by((1.0, 0.0), 12.0 / 144.0, &open_field, &single_cell());
+        }
+
+        assert!((slow_frames.x - 12.0).abs() < 0.01);
+        assert!((fast_frames.x - 12.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn small_steps_accumulate_into_cells() {
+        let open_field = collision(&["     "]);
+        let mut position = CharacterPosition::new(0, 0);
+
+        position.move_by((1.0, 0.0), 0.4, &open_field, &single_cell());
+        assert_eq!(position.cell(), (0, 0));
+        position.move_by((1.0, 0.0), 0.4, &open_field, &single_cell());
+        position.move_by((1.0, 0.0), 0.4, &open_field, &single_cell());
+        assert_eq!(position.cell(), (1, 0));
+    }
+
+    #[test]
+    fn long_frames_do_not_tunnel_through_walls() {
+        let walled = collision(&["   x      "]);
+        let mut position = CharacterPosition::new(0, 0);
+
+        position.move_by((1.0, 0.0), 8.0, &walled, &single_cell());
+
+        assert_eq!(position.cell(), (2, 0));
+    }
+
+    #[test]
+    fn blocked_axis_still_lets_the_other_one_slide() {
+        let walled = collision(&["  x", "   ", "   "]);
+        let mut position = CharacterPosition::new(1, 0);
+
+        position.move_by((1.0, 1.0), 1.5, &walled, &single_cell());
+
+        assert_eq!(position.cell(), (1, 1));
+    }
+}
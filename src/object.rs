@@ -0,0 +1,162 @@
+use std::collections::HashSet; // Import HashSet collection
+use std::fmt; // Import formatting traits
+
+use crate::map::{GameMap, MapError}; // Import map file handling
+use crate::parser; // Import noun matching
+
+/// What happens when the character uses an object
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ObjectAction {
+    Describe, // Show the description again
+    Say(String), // Show a message
+    Talk(String), // Start the conversation of a dialogue
+    Item { item: String, quantity: u32 }, // Lies on the landscape until picked up
+    Coins(u32), // Lie on the landscape until picked up
+}
+
+/// A named part of the landscape the character can look at and use
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RoomObject {
+    pub glyph: char, // Marks the object's cells in the objects layer
+    pub name: String,
+    pub description: String,
+    pub action: ObjectAction,
+    pub cells: HashSet<(i32, i32)>, // Landscape cells (column, row) the object covers
+}
+
+impl fmt::Display for ObjectAction {
+    // Write the action as a map file gives it, empty for `Describe`
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ObjectAction::Describe => Ok(()),
+            ObjectAction::Say(text) => write!(f, "say {}", text),
+            ObjectAction::Talk(dialogue) => write!(f, "talk {}", dialogue),
+            ObjectAction::Item { item, quantity } => write!(f, "item {} {}", item, quantity),
+            ObjectAction::Coins(quantity) => write!(f, "coins {}", quantity),
+        }
+    }
+}
+
+impl RoomObject {
+    /// Whether the player may call the object `noun`: its name, or one word of it, in any letter case
+    pub fn is_called(&self, noun: &str) -> bool {
+        parser::is_called(&self.name, noun)
+    }
+
+    /// Whether the object covers any of `cells`
+    pub fn covers_any(&self, mut cells: impl Iterator<Item = (i32, i32)>) -> bool {
+        cells.any(|cell| self.cells.contains(&cell))
+    }
+}
+
+/// Load the objects of a room map
+///
+/// Every `object X = name | description | action` metadata key describes the
+/// landscape cells marked `X` in the optional `objects` layer. The action is
+/// optional: `say <text>` shows a message, `talk <dialogue>` starts a
+/// conversation and `item <item> [quantity]` makes the object items lying
+/// on the landscape, picked up when used, just like `coins <quantity>`.
+/// Without one the description is shown again.
+pub fn load_objects(game_map: &mut GameMap) -> Result<Vec<RoomObject>, MapError> {
+    let mut objects = Vec::new();
+    for (key, value) in &game_map.metadata {
+        let Some(glyph) = key.strip_prefix("object ") else {
+            continue;
+        };
+        let invalid_metadata = || MapError::InvalidMetadata {
+            path: game_map.path.clone(),
+            key: key.clone(),
+            value: value.clone(),
+        };
+        let mut glyphs = glyph.trim().chars();
+        let (Some(glyph), None) = (glyphs.next(), glyphs.next()) else {
+            return Err(invalid_metadata());
+        };
+        let (name, description, action) = parse_object(value).ok_or_else(invalid_metadata)?;
+        objects.push(RoomObject {
+            glyph,
+            name,
+            description,
+            action,
+            cells: HashSet::new(),
+        });
+    }
+    objects.sort_by_key(|object| object.glyph); // Keep a stable order, whatever the metadata order
+
+    if let Some(objects_layer) = game_map.layers.remove("objects") {
+        for (row, line) in objects_layer.rows.iter().enumerate() {
+            for (col, glyph) in line.chars().enumerate().filter(|(_, glyph)| *glyph != ' ') {
+                let object = objects
+                    .iter_mut()
+                    .find(|object| object.glyph == glyph)
+                    .ok_or_else(|| MapError::UnknownObject {
+                        path: game_map.path.clone(),
+                        glyph,
+                    })?;
+                object.cells.insert((col as i32, row as i32));
+            }
+        }
+    }
+    Ok(objects)
+}
+
+/// Parse a `name | description | action` object definition, as in map files and saved games
+pub fn parse_object(value: &str) -> Option<(String, String, ObjectAction)> {
+    let mut parts = value.splitn(3, '|').map(str::trim);
+    let name = parts.next().filter(|name| !name.is_empty())?;
+    let description = parts.next().filter(|description| !description.is_empty())?;
+    let action = match parts.next().map(|action| action.split_once(' ')) {
+        None => ObjectAction::Describe,
+        Some(Some(("say", text))) => ObjectAction::Say(text.trim().to_string()),
+        Some(Some(("talk", dialogue))) => ObjectAction::Talk(dialogue.trim().to_string()),
+        Some(Some(("coins", quantity))) => ObjectAction::Coins(quantity.trim().parse().ok().filter(|quantity| *quantity > 0)?),
+        Some(Some(("item", item))) => match item.split_whitespace().collect::<Vec<_>>().as_slice() {
+            [item] => ObjectAction::Item { item: item.to_string(), quantity: 1 },
+            [item, quantity] => ObjectAction::Item {
+                item: item.to_string(),
+                quantity: quantity.parse().ok().filter(|quantity| *quantity > 0)?,
+            },
+            _ => return None,
+        },
+        Some(_) => return None,
+    };
+    Some((name.to_string(), description.to_string(), action))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn load(source: &str) -> Result<Vec<RoomObject>, MapError> {
+        load_objects(&mut GameMap::parse(source, Path::new("test.map")).unwrap())
+    }
+
+    #[test]
+    fn objects_cover_the_cells_marked_with_their_glyph() {
+        let objects = load(
+            "object t = Old tree | A tall tree. | say The leaves rustle.\n\
+             object s = Sign | It says START.\n\
+             object w = Wool | Soft. | item wool 3\n\
+             [objects]\nsize = 4x2\n|tt s|\n| t w|\n",
+        )
+        .unwrap();
+
+        assert_eq!(objects[0].name, "Sign");
+        assert_eq!(objects[0].action, ObjectAction::Describe);
+        assert_eq!(objects[1].action, ObjectAction::Say("The leaves rustle.".to_string()));
+        assert_eq!(objects[2].action, ObjectAction::Item { item: "wool".to_string(), quantity: 3 });
+        assert_eq!(objects[1].cells, HashSet::from([(0, 0), (1, 0), (1, 1)]));
+        assert!(objects[1].is_called("TREE") && objects[1].is_called("old tree") && !objects[1].is_called("oak"));
+        assert!(objects[1].covers_any([(5, 5), (1, 1)].into_iter()));
+    }
+
+    #[test]
+    fn unknown_glyphs_and_actions_are_errors() {
+        let unknown_glyph = load("object t = Tree | Tall.\n[objects]\nsize = 2x1\n|tx|\n").unwrap_err();
+        assert_eq!(unknown_glyph.to_string(), "test.map: objects layer marks `x`, but no `object x` key describes it");
+
+        let unknown_action = load("object t = Tree | Tall. | climb it\n").unwrap_err();
+        assert!(matches!(unknown_action, MapError::InvalidMetadata { .. }));
+    }
+}
@@ -0,0 +1,165 @@
+use std::fmt; // Import formatting traits
+
+use crate::save::SAVE_SLOTS; // Import the number of save slots
+use crate::world::Direction; // Import compass directions
+
+/// What the player asked for on the input line
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Action {
+    Look(Option<String>), // Look around, or at something
+    Go(Direction),
+    Take(String),
+    Use(String),
+    Drop(String),
+    Inventory,
+    Talk,
+    Save(usize), // Save the game in a slot, numbered from 1
+    Load(usize), // Load the game in a slot, numbered from 1
+    Help,
+}
+
+/// Why a typed command couldn't be understood
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    Empty,
+    UnknownVerb(String),
+    MissingNoun(&'static str), // The verb, as the player would type it
+    UnknownDirection(String),
+    UnknownSlot(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "Type a command, or HELP."),
+            ParseError::UnknownVerb(verb) => write!(f, "I don't know how to \"{}\".", verb),
+            ParseError::MissingNoun(verb) => write!(f, "What do you want to {}?", verb),
+            ParseError::UnknownDirection(direction) => write!(f, "\"{}\" is not a direction.", direction),
+            ParseError::UnknownSlot(slot) => write!(f, "There is no save slot \"{}\". Try 1 to {}.", slot, SAVE_SLOTS),
+        }
+    }
+}
+
+// Words left out of commands, so "look at the tree" reads as "look tree"
+const NOISE_WORDS: [&str; 6] = ["a", "an", "the", "at", "to", "with"];
+// Verbs and the word left out right after them, so "pick up" reads as "pick"
+// while "go up" still names a direction
+const VERB_PARTICLES: [(&str, &str); 2] = [("pick", "up"), ("put", "down")];
+
+/// Parse a typed command in the style of classic text adventures
+///
+/// Commands are a verb, optionally followed by a noun, in any letter case:
+/// `look`, `go east` (or just `east` or `e`), `take wool`, `drop wool`,
+/// `use tree`, `inventory`, `save 2` and `load 2` (slot 1 if left out).
+/// Verbs and directions have the usual synonyms and abbreviations (`up`
+/// and `down` walk north and south, as on the screen), and
+/// articles and prepositions are skipped, so `pick up the sheep` means
+/// `take sheep`.
+pub fn parse(input: &str) -> Result<Action, ParseError> {
+    let lowercase = input.to_lowercase();
+    let words: Vec<&str> = lowercase
+        .split_whitespace()
+        .filter(|word| !NOISE_WORDS.contains(word))
+        .collect();
+    let Some((&verb, mut rest)) = words.split_first() else {
+        return Err(ParseError::Empty);
+    };
+    if let Some((&particle, after_particle)) = rest.split_first() {
+        if VERB_PARTICLES.contains(&(verb, particle)) {
+            rest = after_particle;
+        }
+    }
+    let noun = (!rest.is_empty()).then(|| rest.join(" "));
+
+    if let Some(direction) = parse_direction(verb) {
+        return Ok(Action::Go(direction));
+    }
+    match verb {
+        "look" | "l" | "examine" | "x" => Ok(Action::Look(noun)),
+        "go" | "walk" | "move" => {
+            let noun = noun.ok_or(ParseError::MissingNoun("go"))?;
+            parse_direction(&noun)
+                .map(Action::Go)
+                .ok_or(ParseError::UnknownDirection(noun))
+        }
+        "take" | "get" | "pick" | "grab" => noun.map(Action::Take).ok_or(ParseError::MissingNoun("take")),
+        "use" | "touch" | "push" | "open" => noun.map(Action::Use).ok_or(ParseError::MissingNoun("use")),
+        "drop" | "put" | "leave" => noun.map(Action::Drop).ok_or(ParseError::MissingNoun("drop")),
+        "inventory" | "inv" | "i" => Ok(Action::Inventory),
+        "talk" | "speak" | "chat" => Ok(Action::Talk),
+        "save" => parse_slot(noun).map(Action::Save),
+        "load" | "restore" => parse_slot(noun).map(Action::Load),
+        "help" | "?" => Ok(Action::Help),
+        _ => Err(ParseError::UnknownVerb(verb.to_string())),
+    }
+}
+
+/// Whether the player may call something named `name` by `noun`: its
+/// whole name, or one word of it, in any letter case
+pub fn is_called(name: &str, noun: &str) -> bool {
+    let name = name.to_lowercase();
+    let noun = noun.to_lowercase();
+    name == noun || name.split_whitespace().any(|word| word == noun)
+}
+
+// Function to parse the number of a save slot, slot 1 if there is none
+fn parse_slot(noun: Option<String>) -> Result<usize, ParseError> {
+    let Some(noun) = noun else {
+        return Ok(1);
+    };
+    let slot = noun.strip_prefix("slot").unwrap_or(&noun).trim();
+    slot.parse()
+        .ok()
+        .filter(|slot| (1..=SAVE_SLOTS).contains(slot))
+        .ok_or(ParseError::UnknownSlot(noun.clone()))
+}
+
+// Function to parse a direction or its abbreviation
+fn parse_direction(word: &str) -> Option<Direction> {
+    match word {
+        "north" | "n" | "up" => Some(Direction::North),
+        "south" | "s" | "down" => Some(Direction::South),
+        "east" | "e" | "right" => Some(Direction::East),
+        "west" | "w" | "left" => Some(Direction::West),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verbs_have_synonyms_and_skip_noise_words() {
+        assert_eq!(parse("LOOK"), Ok(Action::Look(None)));
+        assert_eq!(parse("examine the old tree"), Ok(Action::Look(Some("old tree".to_string()))));
+        assert_eq!(parse("pick up the sheep"), Ok(Action::Take("sheep".to_string())));
+        assert_eq!(parse("get sheep"), Ok(Action::Take("sheep".to_string())));
+        assert_eq!(parse("push the tree"), Ok(Action::Use("tree".to_string())));
+        assert_eq!(parse("put down the wool"), Ok(Action::Drop("wool".to_string())));
+        assert_eq!(parse("i"), Ok(Action::Inventory));
+        assert_eq!(parse("talk to the keeper"), Ok(Action::Talk));
+        assert_eq!(parse("save"), Ok(Action::Save(1)));
+        assert_eq!(parse("restore slot 2"), Ok(Action::Load(2)));
+    }
+
+    #[test]
+    fn directions_can_be_typed_alone_or_after_go() {
+        assert_eq!(parse("go east"), Ok(Action::Go(Direction::East)));
+        assert_eq!(parse("walk to the south"), Ok(Action::Go(Direction::South)));
+        assert_eq!(parse("n"), Ok(Action::Go(Direction::North)));
+        assert_eq!(parse("  West "), Ok(Action::Go(Direction::West)));
+        assert_eq!(parse("go up"), Ok(Action::Go(Direction::North)));
+        assert_eq!(parse("go down"), Ok(Action::Go(Direction::South)));
+        assert_eq!(parse("down"), Ok(Action::Go(Direction::South)));
+    }
+
+    #[test]
+    fn errors_explain_what_was_not_understood() {
+        assert_eq!(parse("   "), Err(ParseError::Empty));
+        assert_eq!(parse("dance"), Err(ParseError::UnknownVerb("dance".to_string())));
+        assert_eq!(parse("take the"), Err(ParseError::MissingNoun("take")));
+        assert_eq!(parse("go home").unwrap_err().to_string(), "\"home\" is not a direction.");
+        assert_eq!(parse("save 9").unwrap_err().to_string(), "There is no save slot \"9\". Try 1 to 3.");
+    }
+}
@@ -0,0 +1,637 @@
+use std::collections::HashSet; // Import HashSet collection
+
+use sdl2::pixels::Color; // Import SDL2 color handling
+use sdl2::rect::Rect; // Import SDL2 rectangle handling
+use sdl2::render::{Canvas, RenderTarget}; // Import SDL2 render targets
+
+use crate::game::GameState; // Import the game state
+use crate::glyph_atlas::GlyphAtlas; // Import the glyph atlas
+use crate::inventory::{COIN_COLOR, COIN_GLYPH}; // Import how coins look
+use crate::map::Layer; // Import map layers
+use crate::markup::{display_width, tag_length, TEXT_COLOR}; // Import textbox markup
+use crate::object::ObjectAction; // Import interactable objects
+use crate::textbox::{TextboxView, PAGE_LINES}; // Import the textbox view
+use crate::{BASE_HEIGHT, BASE_WIDTH, CHAR_HEIGHT, CHAR_WIDTH, TEXT_AREA_HEIGHT}; // Import layout constants
+
+pub const TEXTBOX_ROW: u32 = BASE_HEIGHT / CHAR_HEIGHT - TEXT_AREA_HEIGHT; // First grid row of the textbox
+pub const TEXTBOX_START_COL: u32 = 2; // Start column of the text in a textbox, after the frame and a margin
+pub const CONTINUE_INDICATOR: char = '▼'; // Blinks while the textbox waits for the player to continue
+const FRAME_COLOR: Color = Color::GRAY; // Color of the textbox frame and scrollbar
+const HOVER_COLOR: Color = Color::CYAN; // Color of the object under the mouse pointer
+const INPUT_PROMPT: &str = "> "; // Shown in front of the command being typed
+const INPUT_CURSOR: char = '█'; // Shown after the command being typed
+
+// The renderers draw onto any SDL render target: the window canvas when
+// playing, or a software canvas over a `Surface` when running headless.
+
+/// Render a whole frame of the game
+pub fn render_frame<T: RenderTarget>(
+    game_state: &GameState,
+    glyph_atlas: &mut GlyphAtlas,
+    canvas: &mut Canvas<T>,
+    scale_x: f32,
+    scale_y: f32,
+) {
+    // Clear the canvas with a black background
+    canvas.set_draw_color(Color::BLACK);
+    canvas.clear();
+
+    // Render the background and landscape of the current room
+    let room = game_state.world.current_room();
+    render_background_and_landscape(
+        &room.background,
+        &room.landscape,
+        glyph_atlas,
+        canvas,
+        scale_x,
+        scale_y,
+        &room.revealed_positions,
+    );
+
+    // Render the items lying in the room, and the object under the mouse pointer over them
+    render_items(game_state, glyph_atlas, canvas, scale_x, scale_y);
+    render_hovered_object(game_state, glyph_atlas, canvas, scale_x, scale_y);
+
+    // Render the character
+    let (character_x, character_y) = game_state.character_cell();
+    render_character(
+        &game_state.character_picture.rows,
+        game_state.character_picture.color,
+        glyph_atlas,
+        canvas,
+        scale_x,
+        scale_y,
+        (character_x as isize, character_y as isize),
+    );
+
+    // Render the textbox
+    render_textbox(&game_state.textbox_view(), TEXTBOX_FRAME, glyph_atlas, canvas, scale_x, scale_y);
+
+    // Render the inventory, the slot menu and the message history over everything else while they are open
+    if let Some(inventory_view) = game_state.inventory_view() {
+        render_textbox(&inventory_view, INVENTORY_FRAME, glyph_atlas, canvas, scale_x, scale_y);
+    }
+    if let Some(slot_menu_view) = game_state.slot_menu_view() {
+        render_textbox(&slot_menu_view, SLOT_MENU_FRAME, glyph_atlas, canvas, scale_x, scale_y);
+    }
+    if let Some(history_view) = game_state.history_view() {
+        render_textbox(&history_view, HISTORY_FRAME, glyph_atlas, canvas, scale_x, scale_y);
+    }
+}
+
+// Function to render the background and landscape
+pub fn render_background_and_landscape<T: RenderTarget>(
+    background_picture: &Layer,
+    landscape_picture: &Layer,
+    glyph_atlas: &mut GlyphAtlas,
+    canvas: &mut Canvas<T>,
+    scale_x: f32,
+    scale_y: f32,
+    revealed_positions: &HashSet<(usize, usize)>,
+) {
+    // Render the background grid
+    render_grid(&background_picture.rows, glyph_atlas, canvas, scale_x, scale_y, background_picture.color);
+    // Render the landscape
+    render_landscape(
+        landscape_picture,
+        glyph_atlas,
+        canvas,
+        scale_x,
+        scale_y,
+        revealed_positions,
+    );
+}
+
+// Function to render a grid of characters
+pub fn render_grid<T: RenderTarget>(
+    grid: &[String],
+    glyph_atlas: &mut GlyphAtlas,
+    canvas: &mut Canvas<T>,
+    scale_x: f32,
+    scale_y: f32,
+    color: Color,
+) {
+    for (row, line) in grid.iter().enumerate() { // Iterate over each row
+        for (col, char_to_render) in line.chars().enumerate() { // Iterate over each character in the row
+            let dest_rect = Rect::new(
+                (col as f32 * CHAR_WIDTH as f32 * scale_x) as i32, // Calculate x position
+                (row as f32 * CHAR_HEIGHT as f32 * scale_y) as i32, // Calculate y position
+                (CHAR_WIDTH as f32 * scale_x) as u32, // Calculate width
+                (CHAR_HEIGHT as f32 * scale_y) as u32, // Calculate height
+            );
+            glyph_atlas.draw_char(canvas, char_to_render, color, dest_rect).unwrap(); // Copy the glyph from the atlas to the canvas
+        }
+    }
+}
+
+// Function to render the landscape with revealed positions
+pub fn render_landscape<T: RenderTarget>(
+    landscape_picture: &Layer,
+    glyph_atlas: &mut GlyphAtlas,
+    canvas: &mut Canvas<T>,
+    scale_x: f32,
+    scale_y: f32,
+    revealed_positions: &HashSet<(usize, usize)>,
+) {
+    for (row, line) in landscape_picture.rows.iter().enumerate() { // Iterate over each row
+        for (col, char_to_render) in line.chars().enumerate() { // Iterate over each character in the row
+            if revealed_positions.contains(&(row, col)) { // Check if position is revealed
+                let dest_rect = Rect::new(
+                    (col as f32 * CHAR_WIDTH as f32 * scale_x) as i32, // Calculate x position
+                    (row as f32 * CHAR_HEIGHT as f32 * scale_y) as i32, // Calculate y position
+                    (CHAR_WIDTH as f32 * scale_x) as u32, // Calculate width
+                    (CHAR_HEIGHT as f32 * scale_y) as u32, // Calculate height
+                );
+                glyph_atlas.draw_char(canvas, char_to_render, landscape_picture.color, dest_rect).unwrap(); // Copy the glyph from the atlas to the canvas
+            }
+        }
+    }
+}
+
+// Function to render the character
+pub fn render_character<T: RenderTarget>(
+    character_picture: &[String],
+    color: Color,
+    glyph_atlas: &mut GlyphAtlas,
+    canvas: &mut Canvas<T>,
+    scale_x: f32,
+    scale_y: f32,
+    character_position: (isize, isize),
+) {
+    let (character_x, character_y) = character_position; // Get character position
+
+    for (row, line) in character_picture.iter().enumerate() { // Iterate over each row
+        for (col, char_to_render) in line.chars().enumerate() { // Iterate over each character in the row
+            let dest_x = character_x.checked_add(col as isize).expect("Overflow occurred") as f32 * CHAR_WIDTH as f32 * scale_x; // Calculate x position
+            let dest_y = character_y.checked_add(row as isize).expect("Overflow occurred") as f32 * CHAR_HEIGHT as f32 * scale_y; // Calculate y position
+
+            let dest_rect = Rect::new(
+                dest_x as i32, // Set x position
+                dest_y as i32, // Set y position
+                (CHAR_WIDTH as f32 * scale_x) as u32, // Set width
+                (CHAR_HEIGHT as f32 * scale_y) as u32, // Set height
+            );
+            //println!("Character X position{}", dest_x);
+            glyph_atlas.draw_char(canvas, char_to_render, color, dest_rect).unwrap(); // Copy the glyph from the atlas to the canvas
+        }
+    }
+}
+
+/// Wrap text into lines of at most `max_width` columns
+///
+/// Every character takes one column, as every glyph fills one grid cell, so
+/// box-drawing and block glyphs count the same as letters, while markup
+/// tags take none and stay in the text. Explicit newlines and runs of spaces
+/// are kept; the spaces at a line break are dropped, and words longer than
+/// a whole line are broken wherever the line ends.
+pub fn wrap_text(text: &str, max_width: usize) -> String {
+    let max_width = max_width.max(1); // A zero width could never fit a character
+    let mut wrapped_lines: Vec<String> = Vec::new();
+
+    for paragraph in text.split('\n') {
+        let mut line = String::new();
+        let mut line_width = 0;
+        let mut pending_spaces = 0; // Spaces seen since the last word, placed once the next word fits
+
+        for (index, word) in split_words(paragraph).into_iter().enumerate() {
+            if index > 0 {
+                pending_spaces += 1; // The space split on
+            }
+            if word.is_empty() {
+                continue; // Part of a run of spaces
+            }
+
+            let word_width = display_width(word);
+            if line_width > 0 && line_width + pending_spaces + word_width > max_width {
+                // Move the word to a new line, dropping the spaces at the break
+                wrapped_lines.push(std::mem::take(&mut line));
+                line_width = 0;
+                pending_spaces = 0;
+            }
+            // Indentation is shortened to leave room for the word
+            let spaces = if line_width == 0 { pending_spaces.min(max_width - 1) } else { pending_spaces };
+            line.extend(std::iter::repeat_n(' ', spaces));
+            line_width += spaces;
+            pending_spaces = 0;
+
+            // Place the word, breaking it if it is longer than the rest of the line
+            let mut rest = word;
+            while let Some(char_in_word) = rest.chars().next() {
+                if let Some(length) = tag_length(rest) {
+                    line.push_str(&rest[..length]); // Tags take no room
+                    rest = &rest[length..];
+                    continue;
+                }
+                if line_width == max_width {
+                    wrapped_lines.push(std::mem::take(&mut line));
+                    line_width = 0;
+                }
+                line.push(char_in_word);
+                line_width += 1;
+                rest = &rest[char_in_word.len_utf8()..];
+            }
+        }
+
+        // Keep trailing spaces, so that a line of spaces still takes up a line
+        let spaces = pending_spaces.min(max_width - line_width);
+        line.extend(std::iter::repeat_n(' ', spaces));
+        wrapped_lines.push(line);
+    }
+
+    wrapped_lines.join("\n")
+}
+
+// Function to split a paragraph at every space outside of markup tags
+fn split_words(paragraph: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let mut word_start = 0;
+    let mut index = 0;
+    while let Some(char_in_text) = paragraph[index..].chars().next() {
+        if let Some(length) = tag_length(&paragraph[index..]) {
+            index += length; // Spaces inside a tag don't split it
+            continue;
+        }
+        if char_in_text == ' ' {
+            words.push(&paragraph[word_start..index]);
+            word_start = index + 1;
+        }
+        index += char_in_text.len_utf8();
+    }
+    words.push(&paragraph[word_start..]);
+    words
+}
+
+/// One glyph placed in a grid cell
+#[derive(Clone, Debug, PartialEq)]
+pub struct GridCell {
+    pub col: u32,
+    pub row: u32,
+    pub char_in_cell: char,
+    pub color: Color,
+}
+
+/// A framed text area on the screen grid, in grid cells, frame included
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TextboxFrame {
+    pub left: u32,
+    pub top: u32,
+    pub right: u32,
+    pub bottom: u32,
+}
+
+/// The textbox at the bottom of the screen
+pub const TEXTBOX_FRAME: TextboxFrame = TextboxFrame {
+    left: 0,
+    top: TEXTBOX_ROW - 1,
+    right: BASE_WIDTH / CHAR_WIDTH - 1,
+    bottom: TEXTBOX_ROW + PAGE_LINES as u32,
+};
+
+/// The message history, covering the whole screen
+pub const HISTORY_FRAME: TextboxFrame = TextboxFrame {
+    left: 0,
+    top: 0,
+    right: BASE_WIDTH / CHAR_WIDTH - 1,
+    bottom: BASE_HEIGHT / CHAR_HEIGHT - 1,
+};
+
+/// The inventory screen, over the middle of the landscape
+pub const INVENTORY_FRAME: TextboxFrame = TextboxFrame {
+    left: 4,
+    top: 2,
+    right: BASE_WIDTH / CHAR_WIDTH - 5,
+    bottom: 19,
+};
+
+/// The menu of save slots, over the middle of the landscape
+pub const SLOT_MENU_FRAME: TextboxFrame = TextboxFrame {
+    left: 4,
+    top: 5,
+    right: BASE_WIDTH / CHAR_WIDTH - 5,
+    bottom: 12,
+};
+
+impl TextboxFrame {
+    /// The number of text lines that fit inside the frame
+    pub fn visible_lines(&self) -> usize {
+        (self.bottom - self.top - 1) as usize
+    }
+
+    /// Columns of text between the margin and the scrollbar
+    pub fn text_width(&self) -> usize {
+        (self.right - self.left - 4) as usize
+    }
+}
+
+// Function to render the items lying on the revealed landscape of the current room
+pub fn render_items<T: RenderTarget>(
+    game_state: &GameState,
+    glyph_atlas: &mut GlyphAtlas,
+    canvas: &mut Canvas<T>,
+    scale_x: f32,
+    scale_y: f32,
+) {
+    for cell in item_cells(game_state) {
+        let dest_rect = Rect::new(
+            (cell.col as f32 * CHAR_WIDTH as f32 * scale_x) as i32,
+            (cell.row as f32 * CHAR_HEIGHT as f32 * scale_y) as i32,
+            (CHAR_WIDTH as f32 * scale_x) as u32,
+            (CHAR_HEIGHT as f32 * scale_y) as u32,
+        );
+        glyph_atlas.draw_char(canvas, cell.char_in_cell, cell.color, dest_rect).unwrap();
+    }
+}
+
+// Function to render the object under the mouse pointer in the highlight color
+pub fn render_hovered_object<T: RenderTarget>(
+    game_state: &GameState,
+    glyph_atlas: &mut GlyphAtlas,
+    canvas: &mut Canvas<T>,
+    scale_x: f32,
+    scale_y: f32,
+) {
+    for cell in hover_cells(game_state) {
+        let dest_rect = Rect::new(
+            (cell.col as f32 * CHAR_WIDTH as f32 * scale_x) as i32,
+            (cell.row as f32 * CHAR_HEIGHT as f32 * scale_y) as i32,
+            (CHAR_WIDTH as f32 * scale_x) as u32,
+            (CHAR_HEIGHT as f32 * scale_y) as u32,
+        );
+        glyph_atlas.draw_char(canvas, cell.char_in_cell, cell.color, dest_rect).unwrap();
+    }
+}
+
+/// The revealed cells of the object under the mouse pointer, in its glyphs on the landscape but highlighted
+pub fn hover_cells(game_state: &GameState) -> Vec<GridCell> {
+    let Some(object) = game_state.hovered_object() else {
+        return Vec::new();
+    };
+    let room = game_state.world.current_room();
+    let mut cells: Vec<GridCell> = object
+        .cells
+        .iter()
+        .filter(|(col, row)| room.revealed_positions.contains(&(*row as usize, *col as usize)))
+        .filter_map(|&(col, row)| {
+            let char_in_cell = match &object.action {
+                ObjectAction::Item { item, .. } => game_state.items[item].glyph,
+                ObjectAction::Coins(_) => COIN_GLYPH,
+                _ => room.landscape.rows.get(row as usize)?.chars().nth(col as usize)?,
+            };
+            Some(GridCell {
+                col: col as u32,
+                row: row as u32,
+                char_in_cell,
+                color: HOVER_COLOR,
+            })
+        })
+        .collect();
+    cells.sort_by_key(|cell| (cell.row, cell.col)); // Draw in a stable order
+    cells
+}
+
+/// The cell of the screen grid under a pixel of a window scaled by `scale_x` and `scale_y`
+pub fn grid_cell_at(x: i32, y: i32, scale_x: f32, scale_y: f32) -> (i32, i32) {
+    let col = (x as f32 / (CHAR_WIDTH as f32 * scale_x)).floor() as i32;
+    let row = (y as f32 / (CHAR_HEIGHT as f32 * scale_y)).floor() as i32;
+    (col, row)
+}
+
+/// The cells of the items and coins lying on the revealed landscape of the current room, in their own glyphs
+pub fn item_cells(game_state: &GameState) -> Vec<GridCell> {
+    let room = game_state.world.current_room();
+    let mut cells = Vec::new();
+    for object in &room.objects {
+        let (glyph, color) = match &object.action {
+            ObjectAction::Item { item, .. } => (game_state.items[item].glyph, game_state.items[item].color),
+            ObjectAction::Coins(_) => (COIN_GLYPH, COIN_COLOR),
+            _ => continue,
+        };
+        for &(col, row) in &object.cells {
+            if room.revealed_positions.contains(&(row as usize, col as usize)) {
+                cells.push(GridCell {
+                    col: col as u32,
+                    row: row as u32,
+                    char_in_cell: glyph,
+                    color,
+                });
+            }
+        }
+    }
+    cells.sort_by_key(|cell| (cell.row, cell.col)); // Draw in a stable order
+    cells
+}
+
+// Function to render a textbox: its frame, the visible lines, the scrollbar and the continue indicator
+pub fn render_textbox<T: RenderTarget>(
+    view: &TextboxView,
+    frame: TextboxFrame,
+    glyph_atlas: &mut GlyphAtlas,
+    canvas: &mut Canvas<T>,
+    scale_x: f32,
+    scale_y: f32,
+) {
+    // Clear what is behind the textbox, so the frame doesn't mix with the background
+    let TextboxFrame { left, top, right, bottom } = frame;
+    canvas.set_draw_color(Color::BLACK);
+    canvas
+        .fill_rect(Rect::new(
+            (left as f32 * CHAR_WIDTH as f32 * scale_x) as i32,
+            (top as f32 * CHAR_HEIGHT as f32 * scale_y) as i32,
+            ((right - left + 1) as f32 * CHAR_WIDTH as f32 * scale_x) as u32,
+            ((bottom - top + 1) as f32 * CHAR_HEIGHT as f32 * scale_y) as u32,
+        ))
+        .unwrap();
+
+    for cell in textbox_cells(view, frame) {
+        let dest_rect = Rect::new(
+            (cell.col as f32 * CHAR_WIDTH as f32 * scale_x) as i32,
+            (cell.row as f32 * CHAR_HEIGHT as f32 * scale_y) as i32,
+            (CHAR_WIDTH as f32 * scale_x) as u32,
+            (CHAR_HEIGHT as f32 * scale_y) as u32,
+        );
+        glyph_atlas.draw_char(canvas, cell.char_in_cell, cell.color, dest_rect).unwrap();
+    }
+}
+
+/// Every glyph of a textbox, laid out on the screen grid
+pub fn textbox_cells(view: &TextboxView, frame: TextboxFrame) -> Vec<GridCell> {
+    let mut cells = Vec::new();
+    let mut place = |col: u32, row: u32, char_in_cell: char, color: Color| {
+        cells.push(GridCell {
+            col,
+            row,
+            char_in_cell,
+            color,
+        })
+    };
+
+    // The input line, typed into the bottom edge of the frame
+    let TextboxFrame { left, top, right, bottom } = frame;
+    let input_chars: Vec<char> = match &view.input_line {
+        Some(input_line) => format!("{}{}{}", INPUT_PROMPT, input_line, INPUT_CURSOR).chars().collect(),
+        None => Vec::new(),
+    };
+    for (index, char_in_cell) in input_chars.iter().enumerate() {
+        place(left + 1 + index as u32, bottom, *char_in_cell, TEXT_COLOR);
+    }
+
+    // The frame
+    for col in left + 1..right {
+        place(col, top, '─', FRAME_COLOR);
+        if col > left + input_chars.len() as u32 {
+            place(col, bottom, '─', FRAME_COLOR);
+        }
+    }
+    for row in top + 1..bottom {
+        place(left, row, '│', FRAME_COLOR);
+        place(right, row, '│', FRAME_COLOR);
+    }
+    place(left, top, '╭', FRAME_COLOR);
+    place(right, top, '╮', FRAME_COLOR);
+    place(left, bottom, '╰', FRAME_COLOR);
+    place(right, bottom, '╯', FRAME_COLOR);
+
+    // The text
+    for (row, line) in view.lines.iter().enumerate() {
+        for (col, styled_char) in line.iter().enumerate() {
+            place(
+                left + TEXTBOX_START_COL + col as u32,
+                top + 1 + row as u32,
+                styled_char.char_in_cell,
+                styled_char.color,
+            );
+        }
+    }
+
+    // The scrollbar, with arrows at its ends while there is more text that way
+    let scrollbar_col = right - 1;
+    let (first_row, last_row) = (top + 1, bottom - 1);
+    if let Some((thumb_start, thumb_length)) =
+        scrollbar_thumb(view.first_line, frame.visible_lines(), view.total_lines, (last_row - first_row - 1) as usize)
+    {
+        for (index, row) in (first_row + 1..last_row).enumerate() {
+            let is_thumb = (thumb_start..thumb_start + thumb_length).contains(&index);
+            place(scrollbar_col, row, if is_thumb { '█' } else { '░' }, FRAME_COLOR);
+        }
+        let can_scroll_up = view.first_line > 0;
+        let can_scroll_down = view.first_line + frame.visible_lines() < view.total_lines;
+        place(scrollbar_col, first_row, if can_scroll_up { '▲' } else { '░' }, FRAME_COLOR);
+        place(scrollbar_col, last_row, if can_scroll_down { '▼' } else { '░' }, FRAME_COLOR);
+    }
+
+    // The continue indicator, on the bottom edge of the frame
+    if view.shows_continue_indicator {
+        place(right - 2, bottom, CONTINUE_INDICATOR, TEXT_COLOR);
+    }
+
+    cells
+}
+
+/// The first cell and length of a scrollbar thumb on a track of `track_length` cells,
+/// or `None` if all the lines fit without scrolling
+pub fn scrollbar_thumb(first_line: usize, visible_lines: usize, total_lines: usize, track_length: usize) -> Option<(usize, usize)> {
+    if total_lines <= visible_lines || track_length == 0 {
+        return None;
+    }
+    let thumb_length = (track_length * visible_lines / total_lines).clamp(1, track_length);
+    let max_first_line = total_lines - visible_lines;
+    let thumb_start = ((track_length - thumb_length) * first_line.min(max_first_line) + max_first_line / 2) / max_first_line;
+    Some((thumb_start, thumb_length))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wrap(text: &str, max_width: usize) -> Vec<String> {
+        wrap_text(text, max_width).split('\n').map(str::to_string).collect()
+    }
+
+    #[test]
+    fn scrollbar_thumb_is_proportional() {
+        assert_eq!(scrollbar_thumb(0, 8, 8, 6), None); // Everything fits
+        assert_eq!(scrollbar_thumb(0, 8, 16, 6), Some((0, 3)));
+        assert_eq!(scrollbar_thumb(8, 8, 16, 6), Some((3, 3)));
+        assert_eq!(scrollbar_thumb(4, 8, 16, 6), Some((2, 3)));
+        assert_eq!(scrollbar_thumb(100, 8, 1000, 6), Some((1, 1))); // Never thinner than a cell
+    }
+
+    #[test]
+    fn textbox_frame_encloses_the_text() {
+        let view = TextboxView {
+            lines: vec![crate::markup::plain_line("hi")],
+            first_line: 0,
+            total_lines: 1,
+            shows_continue_indicator: false,
+            input_line: Some("go".to_string()),
+        };
+        let cells = textbox_cells(&view, TEXTBOX_FRAME);
+        let char_at = |col, row| {
+            cells
+                .iter()
+                .rev()
+                .find(|cell| (cell.col, cell.row) == (col, row))
+                .map(|cell| cell.char_in_cell)
+        };
+
+        let TextboxFrame { left, top, right, bottom } = TEXTBOX_FRAME;
+        assert_eq!((char_at(left, top), char_at(right, bottom)), (Some('╭'), Some('╯')));
+        assert_eq!(char_at(TEXTBOX_START_COL, TEXTBOX_ROW), Some('h'));
+        assert_eq!(char_at(right - 1, TEXTBOX_ROW), None); // No scrollbar when the text fits
+        let input_line: String = (left + 1..left + 8).filter_map(|col| char_at(col, bottom)).collect();
+        assert_eq!(input_line, "> go█──");
+    }
+
+    #[test]
+    fn words_wrap_at_the_width() {
+        assert_eq!(wrap("the quick brown fox", 10), vec!["the quick", "brown fox"]);
+        assert_eq!(wrap("exactly ten", 11), vec!["exactly ten"]);
+    }
+
+    #[test]
+    fn box_drawing_glyphs_take_one_column_each() {
+        assert_eq!(wrap("▒▒▒▒ │╭╮│ ♠♠", 10), vec!["▒▒▒▒ │╭╮│", "♠♠"]);
+    }
+
+    #[test]
+    fn long_words_are_broken_without_a_leading_newline() {
+        assert_eq!(wrap("abcdefghijkl", 5), vec!["abcde", "fghij", "kl"]);
+        assert_eq!(wrap("ab cdefghijkl", 5), vec!["ab", "cdefg", "hijkl"]);
+        assert_eq!(wrap("▒▒▒▒▒▒▒", 3), vec!["▒▒▒", "▒▒▒", "▒"]);
+    }
+
+    #[test]
+    fn explicit_newlines_are_kept() {
+        assert_eq!(wrap("one\ntwo\n\nthree", 10), vec!["one", "two", "", "three"]);
+    }
+
+    #[test]
+    fn runs_of_spaces_are_kept_inside_lines() {
+        assert_eq!(wrap("Welcome   to it", 20), vec!["Welcome   to it"]);
+        assert_eq!(wrap("  indented", 20), vec!["  indented"]);
+    }
+
+    #[test]
+    fn spaces_at_a_break_are_dropped() {
+        assert_eq!(wrap("left    right", 8), vec!["left", "right"]);
+    }
+
+    #[test]
+    fn a_line_of_spaces_still_takes_a_line() {
+        assert_eq!(wrap("       ", 35), vec!["       "]);
+        assert_eq!(wrap("      ", 4), vec!["    "]);
+        assert_eq!(wrap("", 10), vec![""]);
+    }
+
+    #[test]
+    fn markup_takes_no_columns_and_is_never_split() {
+        assert_eq!(wrap("the {red}sheep{/} shop", 14), vec!["the {red}sheep{/} shop"]);
+        assert_eq!(wrap("ab {pause 500}cd", 4), vec!["ab", "{pause 500}cd"]);
+        assert_eq!(wrap("{red}abcdef{/}", 3), vec!["{red}abc", "def{/}"]);
+    }
+
+    #[test]
+    fn zero_width_still_makes_progress() {
+        assert_eq!(wrap("ab", 0), vec!["a", "b"]);
+    }
+}
@@ -0,0 +1,705 @@
+use std::collections::HashSet; // Import HashSet collection
+use std::fmt; // Import formatting traits
+use std::fs::{self, File}; // Import file system access
+use std::io::{self, Write}; // Import I/O errors and writing
+use std::path::{Path, PathBuf}; // Import path handling
+
+use crate::game::{GameState, TICKS_PER_SECOND}; // Import the game state
+use crate::inventory::{Inventory, ItemStack}; // Import the inventory
+use crate::markup::style_lines; // Import textbox markup
+use crate::message_log::LogEntry; // Import the message history
+use crate::movement::CharacterPosition; // Import character movement
+use crate::object::{parse_object, ObjectAction, RoomObject}; // Import interactable objects
+use crate::textbox::TextboxView; // Import the textbox view
+use crate::world::Direction; // Import compass directions
+
+/// Version of the save file format this game writes
+pub const SAVE_VERSION: u32 = 1;
+/// Slots the player can save games in, numbered from 1
+pub const SAVE_SLOTS: usize = 3;
+/// Autosaves kept, the newest one and the ones before it
+pub const AUTOSAVE_ROTATIONS: usize = 3;
+const TITLE_COLOR: &str = "yellow"; // Markup color of the slot menu's title
+const DAMAGED_COLOR: &str = "red"; // Markup color of slots whose save can't be read
+
+/// Everything about a game in progress that the data files don't hold
+///
+/// Save files are text in the style of the other data files:
+///
+/// ```text
+/// # The Fabricof save file
+/// version = 1                       <- format version, see `SAVE_VERSION`
+/// room = forest                     <- the room the character is in
+/// position = 10.25,4                <- character position in cells
+/// facing = east
+/// ticks = 3600                      <- play time
+/// coins = 5
+/// flag = met_keeper                 <- one line per flag set
+/// item = wool 3                     <- one line per inventory slot
+/// stock = sheep_shop wool 9         <- what is left of a shop's stock entry
+/// message = 120 | You pick up 5 coins.   <- the message history, oldest first
+///
+/// [room forest]
+/// revealed 4 = ..#####              <- revealed cells of landscape row 4
+/// object % at 20,10 = Wool | Soft wool. | item wool 3   <- items lying in the room
+///
+/// end = 15                          <- number of entries before it
+/// ```
+///
+/// A save without its `end` line was cut short while it was written, and
+/// doesn't load.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SaveData {
+    pub room: String, // Name of the current room's file
+    pub position: (f32, f32), // Character position in cells, with sub-cell precision
+    pub facing: Direction,
+    pub ticks: u64,
+    pub coins: u32,
+    pub stacks: Vec<ItemStack>,
+    pub flags: Vec<String>, // Sorted, so the same game always saves the same
+    pub stock: Vec<(String, String, u32)>, // Shop, item and quantity left of every stock entry
+    pub messages: Vec<LogEntry>,
+    pub rooms: Vec<RoomSave>, // Sorted by name
+}
+
+/// The state of one room in a saved game
+#[derive(Clone, Debug, PartialEq)]
+pub struct RoomSave {
+    pub name: String, // Name of the room's file
+    pub revealed_positions: HashSet<(usize, usize)>,
+    pub objects: Vec<RoomObject>, // Items and coins lying in the room, which move as the player picks them up and drops them
+}
+
+/// Whether the slot menu saves to the chosen slot or loads from it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SlotMenuMode {
+    Save,
+    Load,
+}
+
+/// The menu of save slots, open to save or to load a game
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SlotMenu {
+    pub mode: SlotMenuMode,
+    pub selected: usize, // Index of the selected slot, from 0
+    pub summaries: Vec<String>, // What each slot holds, read when the menu was opened
+}
+
+// A `key = value` line of a save file, with the room section it is in
+struct SaveEntry {
+    line: usize,
+    room: Option<String>,
+    key: String,
+    value: String,
+}
+
+/// Errors reported while reading, writing or restoring a saved game
+#[derive(Debug)]
+pub enum SaveError {
+    Io { path: PathBuf, error: io::Error },
+    Syntax { path: PathBuf, line: usize, message: String }, // A save that can't be parsed, or was cut short
+    UnsupportedVersion { path: PathBuf, version: u32 }, // A save written by a newer version of the game
+    UnknownRoom { path: PathBuf, room: String },
+    UnknownItem { path: PathBuf, item: String },
+    UnknownShop { path: PathBuf, shop: String },
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaveError::Io { path, error } => write!(f, "{}: {}", path.display(), error),
+            SaveError::Syntax { path, line, message } => write!(f, "{}:{}: {}", path.display(), line, message),
+            SaveError::UnsupportedVersion { path, version } => {
+                write!(f, "{}: save format version {} is newer than this game", path.display(), version)
+            }
+            SaveError::UnknownRoom { path, room } => write!(f, "{}: unknown room `{}`", path.display(), room),
+            SaveError::UnknownItem { path, item } => write!(f, "{}: unknown item `{}`", path.display(), item),
+            SaveError::UnknownShop { path, shop } => write!(f, "{}: unknown shop `{}`", path.display(), shop),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
+
+impl SaveData {
+    /// Capture the state of a game
+    pub fn capture(game_state: &GameState) -> SaveData {
+        let mut flags: Vec<String> = game_state.flags.iter().cloned().collect();
+        flags.sort();
+
+        let mut shop_names: Vec<&String> = game_state.shops.keys().collect();
+        shop_names.sort();
+        let stock = shop_names
+            .into_iter()
+            .flat_map(|shop_name| {
+                game_state.shops[shop_name]
+                    .stock
+                    .iter()
+                    .map(move |entry| (shop_name.clone(), entry.item.clone(), entry.quantity))
+            })
+            .collect();
+
+        let mut rooms: Vec<RoomSave> = game_state
+            .world
+            .rooms()
+            .map(|(room_name, room)| RoomSave {
+                name: room_name.clone(),
+                revealed_positions: room.revealed_positions.clone(),
+                objects: room.objects.iter().filter(|object| is_lying(object)).cloned().collect(),
+            })
+            .collect();
+        rooms.sort_by(|first, second| first.name.cmp(&second.name));
+
+        SaveData {
+            room: game_state.world.current_room_name().to_string(),
+            position: (game_state.character_position.x, game_state.character_position.y),
+            facing: game_state.facing,
+            ticks: game_state.ticks,
+            coins: game_state.inventory.coins,
+            stacks: game_state.inventory.stacks.clone(),
+            flags,
+            stock,
+            messages: game_state.message_log.entries.clone(),
+            rooms,
+        }
+    }
+
+    /// Put a game back in the saved state, once the save is known to fit the
+    /// game's data files; `path` is only used in error messages
+    ///
+    /// Rooms the save doesn't mention keep their current state.
+    pub fn restore(self, game_state: &mut GameState, path: &Path) -> Result<(), SaveError> {
+        let room_exists = |room_name: &String| game_state.world.rooms().any(|(name, _)| name == room_name);
+        if let Some(room) = std::iter::once(&self.room)
+            .chain(self.rooms.iter().map(|room| &room.name))
+            .find(|room_name| !room_exists(room_name))
+        {
+            return Err(SaveError::UnknownRoom {
+                path: path.to_path_buf(),
+                room: room.clone(),
+            });
+        }
+        let lying_items = self.rooms.iter().flat_map(|room| &room.objects).filter_map(|object| match &object.action {
+            ObjectAction::Item { item, .. } => Some(item),
+            _ => None,
+        });
+        if let Some(item) = self
+            .stacks
+            .iter()
+            .map(|stack| &stack.item)
+            .chain(lying_items)
+            .find(|item| !game_state.items.contains_key(*item))
+        {
+            return Err(SaveError::UnknownItem {
+                path: path.to_path_buf(),
+                item: item.clone(),
+            });
+        }
+        if let Some((shop, _, _)) = self.stock.iter().find(|(shop, _, _)| !game_state.shops.contains_key(shop)) {
+            return Err(SaveError::UnknownShop {
+                path: path.to_path_buf(),
+                shop: shop.clone(),
+            });
+        }
+
+        game_state.world.enter(&self.room);
+        for room_save in self.rooms {
+            let room = game_state.world.room_mut(&room_save.name).expect("room was checked above");
+            room.revealed_positions = room_save.revealed_positions;
+            room.objects.retain(|object| !is_lying(object));
+            room.objects.extend(room_save.objects);
+        }
+        let (x, y) = self.position;
+        game_state.character_position = CharacterPosition { x, y };
+        game_state.facing = self.facing;
+        game_state.ticks = self.ticks;
+        game_state.inventory = Inventory {
+            stacks: self.stacks,
+            coins: self.coins,
+        };
+        game_state.flags = self.flags.into_iter().collect();
+        for (shop, item, quantity) in self.stock {
+            let shop = game_state.shops.get_mut(&shop).expect("shop was checked above");
+            if let Some(entry) = shop.stock.iter_mut().find(|entry| entry.item == item) {
+                entry.quantity = quantity;
+            }
+        }
+        game_state.message_log.entries = self.messages;
+        game_state.message_log.scroll_to_end();
+        Ok(())
+    }
+
+    /// The save as the contents of a save file
+    pub fn to_text(&self) -> String {
+        let (x, y) = self.position;
+        let mut lines = vec![
+            "# The Fabricof save file".to_string(),
+            format!("version = {}", SAVE_VERSION),
+            format!("room = {}", self.room),
+            format!("position = {},{}", x, y),
+            format!("facing = {}", self.facing.name()),
+            format!("ticks = {}", self.ticks),
+            format!("coins = {}", self.coins),
+        ];
+        lines.extend(self.flags.iter().map(|flag| format!("flag = {}", flag)));
+        lines.extend(self.stacks.iter().map(|stack| format!("item = {} {}", stack.item, stack.quantity)));
+        lines.extend(self.stock.iter().map(|(shop, item, quantity)| format!("stock = {} {} {}", shop, item, quantity)));
+        lines.extend(self.messages.iter().map(|entry| format!("message = {} | {}", entry.tick, entry.message)));
+
+        for room in &self.rooms {
+            lines.push(String::new());
+            lines.push(format!("[room {}]", room.name));
+            let width = room.revealed_positions.iter().map(|(_, col)| col + 1).max().unwrap_or(0);
+            let mut rows: Vec<usize> = room.revealed_positions.iter().map(|(row, _)| *row).collect();
+            rows.sort();
+            rows.dedup();
+            for row in rows {
+                let cells: String = (0..width)
+                    .map(|col| if room.revealed_positions.contains(&(row, col)) { '#' } else { '.' })
+                    .collect();
+                lines.push(format!("revealed {} = {}", row, cells.trim_end_matches('.')));
+            }
+            for object in &room.objects {
+                let mut cells: Vec<&(i32, i32)> = object.cells.iter().collect();
+                cells.sort();
+                let cells: Vec<String> = cells.iter().map(|(col, row)| format!("{},{}", col, row)).collect();
+                lines.push(format!(
+                    "object {} at {} = {} | {} | {}",
+                    object.glyph,
+                    cells.join(" "),
+                    object.name,
+                    object.description,
+                    object.action
+                ));
+            }
+        }
+
+        let entry_count = lines
+            .iter()
+            .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with('['))
+            .count();
+        lines.push(String::new());
+        lines.push(format!("end = {}", entry_count));
+        lines.join("\n") + "\n"
+    }
+
+    /// Parse save file contents; `path` is only used in error messages
+    pub fn parse(source: &str, path: &Path) -> Result<SaveData, SaveError> {
+        let syntax_error = |line: usize, message: String| SaveError::Syntax {
+            path: path.to_path_buf(),
+            line,
+            message,
+        };
+
+        // Split the file into entries, remembering the room sections. A line
+        // that isn't an entry is only reported once the save is known to be
+        // complete, as a save cut short usually ends in the middle of a line.
+        let mut entries = Vec::new();
+        let mut room_names: Vec<String> = Vec::new();
+        let mut room = None;
+        let mut malformed_line = None;
+        for (index, line) in source.lines().enumerate() {
+            let line = line.trim_start(); // Keep trailing spaces, which may belong to a message
+            if line.trim_end().is_empty() || line.starts_with('#') {
+                continue; // Skip blank lines and comments
+            }
+            if let Some(room_name) = line.trim_end().strip_prefix("[room ").and_then(|rest| rest.strip_suffix(']')) {
+                room = Some(room_name.trim().to_string());
+                room_names.push(room_name.trim().to_string());
+                continue;
+            }
+            let (key, value) = line.split_once('=').unwrap_or_else(|| {
+                malformed_line.get_or_insert_with(|| syntax_error(index + 1, format!("expected `key = value`, found `{}`", line.trim_end())));
+                (line, "")
+            });
+            entries.push(SaveEntry {
+                line: index + 1,
+                room: room.clone(),
+                key: key.trim().to_string(),
+                value: value.strip_prefix(' ').unwrap_or(value).to_string(), // Only the separator space is dropped
+            });
+        }
+
+        // Check the version, and that the save is complete
+        let version_entry = entries
+            .iter()
+            .find(|entry| entry.room.is_none() && entry.key == "version")
+            .ok_or_else(|| syntax_error(1, "save has no `version`".to_string()))?;
+        let version: u32 = version_entry
+            .value
+            .trim()
+            .parse()
+            .ok()
+            .filter(|version| *version > 0)
+            .ok_or_else(|| syntax_error(version_entry.line, format!("invalid version `{}`", version_entry.value)))?;
+        if version > SAVE_VERSION {
+            return Err(SaveError::UnsupportedVersion {
+                path: path.to_path_buf(),
+                version,
+            });
+        }
+        let end = entries.pop();
+        let is_complete = end.as_ref().is_some_and(|end| end.key == "end" && end.value.trim().parse() == Ok(entries.len()));
+        if !is_complete {
+            let line = end.map_or(1, |end| end.line);
+            return Err(syntax_error(line, "save was cut short".to_string()));
+        }
+        if let Some(error) = malformed_line {
+            return Err(error);
+        }
+
+        let mut save = SaveData {
+            room: String::new(),
+            position: (0.0, 0.0),
+            facing: Direction::South,
+            ticks: 0,
+            coins: 0,
+            stacks: Vec::new(),
+            flags: Vec::new(),
+            stock: Vec::new(),
+            messages: Vec::new(),
+            rooms: Vec::new(),
+        };
+        room_names.dedup();
+        for room_name in room_names {
+            save.rooms.push(RoomSave {
+                name: room_name,
+                revealed_positions: HashSet::new(),
+                objects: Vec::new(),
+            });
+        }
+
+        for entry in &entries {
+            let invalid_value = || syntax_error(entry.line, format!("invalid {} `{}`", entry.key, entry.value));
+            let value = if entry.key == "message" { entry.value.as_str() } else { entry.value.trim() };
+            match &entry.room {
+                None => match entry.key.as_str() {
+                    "version" => {}
+                    "room" => save.room = value.to_string(),
+                    "position" => {
+                        save.position = value
+                            .split_once(',')
+                            .and_then(|(x, y)| Some((x.trim().parse().ok()?, y.trim().parse().ok()?)))
+                            .ok_or_else(invalid_value)?;
+                    }
+                    "facing" => {
+                        save.facing = [Direction::North, Direction::South, Direction::East, Direction::West]
+                            .into_iter()
+                            .find(|direction| direction.name() == value)
+                            .ok_or_else(invalid_value)?;
+                    }
+                    "ticks" => save.ticks = value.parse().map_err(|_| invalid_value())?,
+                    "coins" => save.coins = value.parse().map_err(|_| invalid_value())?,
+                    "flag" => save.flags.push(value.to_string()),
+                    "item" => match value.split_whitespace().collect::<Vec<_>>().as_slice() {
+                        [item, quantity] => save.stacks.push(ItemStack {
+                            item: item.to_string(),
+                            quantity: quantity.parse().map_err(|_| invalid_value())?,
+                        }),
+                        _ => return Err(invalid_value()),
+                    },
+                    "stock" => match value.split_whitespace().collect::<Vec<_>>().as_slice() {
+                        [shop, item, quantity] => {
+                            let quantity = quantity.parse().map_err(|_| invalid_value())?;
+                            save.stock.push((shop.to_string(), item.to_string(), quantity));
+                        }
+                        _ => return Err(invalid_value()),
+                    },
+                    "message" => {
+                        let (tick, message) = value.split_once('|').ok_or_else(invalid_value)?;
+                        save.messages.push(LogEntry {
+                            tick: tick.trim().parse().map_err(|_| invalid_value())?,
+                            message: message.strip_prefix(' ').unwrap_or(message).to_string(), // Keep the message's own spaces
+                        });
+                    }
+                    key => return Err(syntax_error(entry.line, format!("unknown save property `{}`", key))),
+                },
+                Some(room_name) => {
+                    let room = save
+                        .rooms
+                        .iter_mut()
+                        .find(|room| &room.name == room_name)
+                        .expect("every room section is listed");
+                    if let Some(row) = entry.key.strip_prefix("revealed ") {
+                        let row: usize = row.trim().parse().map_err(|_| invalid_value())?;
+                        for (col, cell) in value.chars().enumerate() {
+                            match cell {
+                                '#' => room.revealed_positions.insert((row, col)),
+                                '.' => false,
+                                _ => return Err(invalid_value()),
+                            };
+                        }
+                    } else if let Some(placement) = entry.key.strip_prefix("object ") {
+                        let object = parse_placed_object(placement, value).ok_or_else(invalid_value)?;
+                        room.objects.push(object);
+                    } else {
+                        return Err(syntax_error(entry.line, format!("unknown room property `{}`", entry.key)));
+                    }
+                }
+            }
+        }
+
+        if save.room.is_empty() {
+            return Err(syntax_error(1, "save has no `room`".to_string()));
+        }
+        Ok(save)
+    }
+}
+
+impl SlotMenu {
+    /// Open the menu, summing up the game in every slot of `saves_dir`;
+    /// `room_name` gives the name shown for a room's file
+    pub fn open(mode: SlotMenuMode, saves_dir: &Path, room_name: impl Fn(&str) -> String) -> SlotMenu {
+        let summaries = (1..=SAVE_SLOTS)
+            .map(|slot| match read_slot(saves_dir, slot) {
+                Ok(Some(save)) => format!("{}, {}", room_name(&save.room), play_time(save.ticks)),
+                Ok(None) => "Empty".to_string(),
+                Err(_) => format!("{{{}}}Damaged{{/}}", DAMAGED_COLOR),
+            })
+            .collect();
+        SlotMenu {
+            mode,
+            selected: 0,
+            summaries,
+        }
+    }
+
+    /// Move the selected slot up or down by `offset`, wrapping around
+    pub fn move_selection(&mut self, offset: isize) {
+        self.selected = (self.selected as isize + offset).rem_euclid(SAVE_SLOTS as isize) as usize;
+    }
+
+    /// The menu: a title, then one line per slot with the selected one marked
+    pub fn view(&self) -> TextboxView {
+        let title = match self.mode {
+            SlotMenuMode::Save => format!("{{{}}}Save game{{/}}", TITLE_COLOR),
+            SlotMenuMode::Load => format!("{{{}}}Load game{{/}}", TITLE_COLOR),
+        };
+        let mut lines = vec![title, String::new()];
+        for (index, summary) in self.summaries.iter().enumerate() {
+            let marker = if index == self.selected { '>' } else { ' ' };
+            lines.push(format!("{} Slot {}  {}", marker, index + 1, summary));
+        }
+        let lines = style_lines(lines.iter().map(String::as_str));
+
+        let total_lines = lines.len();
+        TextboxView {
+            lines,
+            first_line: 0,
+            total_lines,
+            shows_continue_indicator: false,
+            input_line: None,
+        }
+    }
+}
+
+/// The file a save slot is kept in, for slots numbered from 1
+pub fn slot_path(saves_dir: &Path, slot: usize) -> PathBuf {
+    saves_dir.join(format!("slot{}.sav", slot))
+}
+
+/// Write a game to a save slot, creating the saves directory if needed, and return the file written
+pub fn write_slot(saves_dir: &Path, slot: usize, save: &SaveData) -> Result<PathBuf, SaveError> {
+    let path = slot_path(saves_dir, slot);
+    let temp_path = write_temp_file(&path, &save.to_text())?;
+    replace_file(&temp_path, &path)?;
+    Ok(path)
+}
+
+/// Read the game in a save slot, or `None` if nothing was saved there
+pub fn read_slot(saves_dir: &Path, slot: usize) -> Result<Option<SaveData>, SaveError> {
+    read_save(&slot_path(saves_dir, slot))
+}
+
+/// The file an autosave is kept in, from 0 for the newest to `AUTOSAVE_ROTATIONS` - 1 for the oldest
+pub fn autosave_path(saves_dir: &Path, rotation: usize) -> PathBuf {
+    match rotation {
+        0 => saves_dir.join("autosave.sav"),
+        _ => saves_dir.join(format!("autosave.{}.sav", rotation)),
+    }
+}
+
+/// Write a game as the newest autosave, keeping the ones before it as
+/// older rotations and dropping the oldest, and return the file written
+///
+/// The new save is written in full before any older one moves, so a crash
+/// at any point leaves at least the autosaves there were before.
+pub fn write_autosave(saves_dir: &Path, save: &SaveData) -> Result<PathBuf, SaveError> {
+    let path = autosave_path(saves_dir, 0);
+    let temp_path = write_temp_file(&path, &save.to_text())?;
+    for rotation in (1..AUTOSAVE_ROTATIONS).rev() {
+        let older_path = autosave_path(saves_dir, rotation - 1);
+        if older_path.exists() {
+            replace_file(&older_path, &autosave_path(saves_dir, rotation))?;
+        }
+    }
+    replace_file(&temp_path, &path)?;
+    Ok(path)
+}
+
+/// Read the game in a save file, or `None` if there is no such file
+pub fn read_save(path: &Path) -> Result<Option<SaveData>, SaveError> {
+    match fs::read_to_string(path) {
+        Ok(source) => SaveData::parse(&source, path).map(Some),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(SaveError::Io {
+            path: path.to_path_buf(),
+            error,
+        }),
+    }
+}
+
+/// Play time as minutes and seconds
+pub fn play_time(ticks: u64) -> String {
+    let seconds = ticks / TICKS_PER_SECOND as u64;
+    format!("{:02}:{:02}", seconds / 60, seconds % 60)
+}
+
+// Function to write the contents of a save next to where it belongs and flush
+// it to the disk, creating the saves directory if needed
+fn write_temp_file(path: &Path, contents: &str) -> Result<PathBuf, SaveError> {
+    let temp_path = path.with_extension("sav.tmp");
+    let io_error = |error| SaveError::Io {
+        path: temp_path.clone(),
+        error,
+    };
+    if let Some(saves_dir) = path.parent() {
+        fs::create_dir_all(saves_dir).map_err(io_error)?;
+    }
+    let mut file = File::create(&temp_path).map_err(io_error)?;
+    file.write_all(contents.as_bytes()).map_err(io_error)?;
+    file.sync_all().map_err(io_error)?;
+    Ok(temp_path)
+}
+
+// Function to move a file over another in one step, so the other is never left half written
+fn replace_file(from: &Path, to: &Path) -> Result<(), SaveError> {
+    fs::rename(from, to).map_err(|error| SaveError::Io {
+        path: to.to_path_buf(),
+        error,
+    })
+}
+
+// Function to parse an object lying in a room: `X at col,row ...` and its definition
+fn parse_placed_object(placement: &str, definition: &str) -> Option<RoomObject> {
+    let mut chars = placement.chars();
+    let glyph = chars.next()?;
+    let cells = chars.as_str().trim().strip_prefix("at ")?;
+    let cells = cells
+        .split_whitespace()
+        .map(|cell| {
+            let (col, row) = cell.split_once(',')?;
+            Some((col.parse().ok()?, row.parse().ok()?))
+        })
+        .collect::<Option<HashSet<(i32, i32)>>>()?;
+    let (name, description, action) = parse_object(definition)?;
+    Some(RoomObject {
+        glyph,
+        name,
+        description,
+        action,
+        cells,
+    })
+}
+
+// Function to tell the objects that move about with the player (items and
+// coins) from those that are part of the landscape
+fn is_lying(object: &RoomObject) -> bool {
+    matches!(object.action, ObjectAction::Item { .. } | ObjectAction::Coins(_))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn save() -> SaveData {
+        SaveData {
+            room: "forest".to_string(),
+            position: (10.25, 4.0),
+            facing: Direction::East,
+            ticks: 3725,
+            coins: 5,
+            stacks: vec![ItemStack {
+                item: "wool".to_string(),
+                quantity: 3,
+            }],
+            flags: vec!["met_keeper".to_string()],
+            stock: vec![("sheep_shop".to_string(), "wool".to_string(), 9)],
+            messages: vec![LogEntry {
+                tick: 120,
+                message: "You pick up {yellow}5{/} coins. | Baa!".to_string(),
+            }],
+            rooms: vec![RoomSave {
+                name: "forest".to_string(),
+                revealed_positions: HashSet::from([(0, 1), (0, 2), (3, 0)]),
+                objects: vec![RoomObject {
+                    glyph: '%',
+                    name: "Wool".to_string(),
+                    description: "Soft wool.".to_string(),
+                    action: ObjectAction::Item {
+                        item: "wool".to_string(),
+                        quantity: 3,
+                    },
+                    cells: HashSet::from([(20, 10), (21, 10)]),
+                }],
+            }],
+        }
+    }
+
+    #[test]
+    fn saves_read_back_as_written() {
+        let text = save().to_text();
+
+        assert!(text.contains("\n[room forest]\nrevealed 0 = .##\nrevealed 3 = #\nobject % at 20,10 21,10 = Wool | Soft wool. | item wool 3\n"));
+        assert_eq!(SaveData::parse(&text, Path::new("slot1.sav")).unwrap(), save());
+    }
+
+    #[test]
+    fn messages_keep_their_leading_and_trailing_spaces() {
+        let mut padded = save();
+        padded.messages = vec![
+            LogEntry {
+                tick: 0,
+                message: "   ".to_string(),
+            },
+            LogEntry {
+                tick: 1,
+                message: "  Welcome!  ".to_string(),
+            },
+        ];
+
+        assert_eq!(SaveData::parse(&padded.to_text(), Path::new("slot1.sav")).unwrap(), padded);
+    }
+
+    #[test]
+    fn saves_cut_short_are_errors() {
+        let text = save().to_text();
+        let cut_short = &text[..text.find("[room forest]").unwrap()];
+
+        let error = SaveData::parse(cut_short, Path::new("slot1.sav")).unwrap_err();
+        assert_eq!(error.to_string(), "slot1.sav:11: save was cut short");
+    }
+
+    #[test]
+    fn newer_and_broken_saves_are_errors() {
+        let newer = SaveData::parse("version = 99\nroom = start\n", Path::new("slot1.sav")).unwrap_err();
+        assert_eq!(newer.to_string(), "slot1.sav: save format version 99 is newer than this game");
+
+        let broken = SaveData::parse("version = 1\nroom = start\nposition = 1\nend = 3\n", Path::new("slot1.sav")).unwrap_err();
+        assert_eq!(broken.to_string(), "slot1.sav:3: invalid position `1`");
+    }
+
+    #[test]
+    fn the_menu_sums_up_every_slot() {
+        let mut menu = SlotMenu {
+            mode: SlotMenuMode::Load,
+            selected: 0,
+            summaries: vec!["Forest, 01:02".to_string(), "Empty".to_string(), "Empty".to_string()],
+        };
+        menu.move_selection(-1);
+
+        let lines: Vec<String> = menu.view().lines.iter().map(|line| crate::markup::line_text(line)).collect();
+        assert_eq!(lines, ["Load game", "", "  Slot 1  Forest, 01:02", "  Slot 2  Empty", "> Slot 3  Empty"]);
+        assert_eq!(play_time(3725), "01:02");
+    }
+}
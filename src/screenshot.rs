@@ -0,0 +1,140 @@
+use std::fs; // Import file system access
+use std::path::{Path, PathBuf}; // Import path handling
+use std::time::{SystemTime, UNIX_EPOCH}; // Import wall clock time for file names
+
+use sdl2::pixels::PixelFormatEnum; // Import SDL2 pixel format handling
+use sdl2::render::{Canvas, RenderTarget}; // Import SDL2 render targets
+use sdl2::surface::Surface; // Import SDL2 surface handling
+
+use crate::game::GameState; // Import the game state
+use crate::render::{item_cells, textbox_cells, TextboxFrame, HISTORY_FRAME, INVENTORY_FRAME, SLOT_MENU_FRAME, TEXTBOX_FRAME}; // Import the screen layout
+use crate::{BASE_HEIGHT, BASE_WIDTH, CHAR_HEIGHT, CHAR_WIDTH}; // Import layout constants
+
+/// A capture of the current frame requested by the player
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Capture {
+    Bitmap, // The rendered pixels, as a BMP
+    TextGrid, // Every layer composited into a grid of characters, as plain text
+}
+
+/// Save the pixels of a canvas as a timestamped BMP in `dir`
+///
+/// Call this after rendering and before presenting, while the canvas still
+/// holds the finished frame.
+pub fn save_bitmap<T: RenderTarget>(canvas: &Canvas<T>, dir: &Path) -> Result<PathBuf, String> {
+    let (width, height) = canvas.output_size()?;
+    let format = PixelFormatEnum::ARGB8888;
+    let mut pixels = canvas.read_pixels(None, format)?;
+    let surface = Surface::from_data(&mut pixels, width, height, width * 4, format)?;
+
+    let path = new_capture_path(dir, "bmp")?;
+    surface.save_bmp(&path)?;
+    Ok(path)
+}
+
+/// Save the current frame as a plain-text character grid in `dir`
+pub fn save_text_grid(game_state: &GameState, dir: &Path) -> Result<PathBuf, String> {
+    let mut text = text_grid(game_state).join("\n");
+    text.push('\n');
+
+    let path = new_capture_path(dir, "txt")?;
+    fs::write(&path, text).map_err(|error| format!("{}: {}", path.display(), error))?;
+    Ok(path)
+}
+
+/// Composite every layer of the current frame into one line per grid row
+///
+/// Layers are stacked in the order they are rendered: background, the
+/// revealed part of the landscape, the items lying on it, the character,
+/// the textbox, and the inventory and message history when they are open.
+/// Spaces are transparent, just like on screen, except inside textboxes.
+pub fn text_grid(game_state: &GameState) -> Vec<String> {
+    let columns = (BASE_WIDTH / CHAR_WIDTH) as usize;
+    let rows = (BASE_HEIGHT / CHAR_HEIGHT) as usize;
+    let mut grid = vec![vec![' '; columns]; rows];
+
+    // Draw a block of lines with its top-left corner at (x, y), clipped to the screen
+    let mut draw = |lines: &[String], (x, y): (i32, i32), is_visible: &dyn Fn(usize, usize) -> bool| {
+        for (row, line) in lines.iter().enumerate() {
+            for (col, char_in_cell) in line.chars().enumerate() {
+                let (grid_x, grid_y) = (x + col as i32, y + row as i32);
+                if char_in_cell == ' ' || grid_x < 0 || grid_y < 0 || !is_visible(row, col) {
+                    continue;
+                }
+                if let Some(cell) = grid.get_mut(grid_y as usize).and_then(|line| line.get_mut(grid_x as usize)) {
+                    *cell = char_in_cell;
+                }
+            }
+        }
+    };
+
+    let room = game_state.world.current_room();
+    draw(&room.background.rows, (0, 0), &|_, _| true);
+    draw(&room.landscape.rows, (0, 0), &|row, col| room.revealed_positions.contains(&(row, col)));
+    for cell in item_cells(game_state) {
+        draw(&[cell.char_in_cell.to_string()], (cell.col as i32, cell.row as i32), &|_, _| true);
+    }
+    draw(&game_state.character_picture.rows, game_state.character_cell(), &|_, _| true);
+
+    // Textboxes cover everything behind them
+    let mut draw_textbox = |view, frame: TextboxFrame| {
+        for line in &mut grid[frame.top as usize..=frame.bottom as usize] {
+            line[frame.left as usize..=frame.right as usize].fill(' ');
+        }
+        for cell in textbox_cells(&view, frame) {
+            grid[cell.row as usize][cell.col as usize] = cell.char_in_cell;
+        }
+    };
+    draw_textbox(game_state.textbox_view(), TEXTBOX_FRAME);
+    if let Some(inventory_view) = game_state.inventory_view() {
+        draw_textbox(inventory_view, INVENTORY_FRAME);
+    }
+    if let Some(slot_menu_view) = game_state.slot_menu_view() {
+        draw_textbox(slot_menu_view, SLOT_MENU_FRAME);
+    }
+    if let Some(history_view) = game_state.history_view() {
+        draw_textbox(history_view, HISTORY_FRAME);
+    }
+
+    grid.into_iter().map(|line| line.into_iter().collect()).collect()
+}
+
+// Function to pick a file name in `dir` from the current time
+fn new_capture_path(dir: &Path, extension: &str) -> Result<PathBuf, String> {
+    fs::create_dir_all(dir).map_err(|error| format!("{}: {}", dir.display(), error))?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    Ok(dir.join(format!(
+        "fabricof-{}-{:03}.{}",
+        now.as_secs(),
+        now.subsec_millis(),
+        extension
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markup::line_text;
+    use crate::render::{TEXTBOX_ROW, TEXTBOX_START_COL};
+    use crate::{MAPS_DIR, START_ROOM};
+
+    #[test]
+    fn text_grid_stacks_every_layer() {
+        let mut game_state = GameState::load(Path::new(MAPS_DIR), START_ROOM).unwrap();
+        game_state.textbox.advance(); // Reveal the first page of the welcome text
+        let grid = text_grid(&game_state);
+
+        assert_eq!(grid.len(), (BASE_HEIGHT / CHAR_HEIGHT) as usize);
+        assert!(grid.iter().all(|line| line.chars().count() == (BASE_WIDTH / CHAR_WIDTH) as usize));
+        assert!(grid[0].starts_with("####")); // Background
+
+        let (x, y) = game_state.character_cell();
+        let head: String = grid[y as usize].chars().skip(x as usize + 1).take(3).collect();
+        assert_eq!(head, "╭#╮"); // Character over the landscape
+
+        let first_line = line_text(&game_state.textbox_view().lines[0]);
+        let textbox: String = grid[TEXTBOX_ROW as usize].chars().skip(TEXTBOX_START_COL as usize).collect();
+        assert!(textbox.starts_with(first_line.as_str()));
+        assert!(grid[TEXTBOX_ROW as usize - 1].starts_with("╭──")); // Textbox frame over the background
+    }
+}
@@ -0,0 +1,377 @@
+use std::collections::HashMap; // Import HashMap collection
+use std::fmt; // Import formatting traits
+use std::fs; // Import file system access
+use std::path::Path; // Import path handling
+
+use crate::inventory::{Inventory, ItemDefinition}; // Import items and the inventory
+use crate::map::MapError; // Import data file errors
+use crate::markup::display_width; // Import textbox markup
+
+const TITLE_COLOR: &str = "yellow"; // Markup color of the shop's name in the menu
+const SOLD_OUT_COLOR: &str = "gray"; // Markup color of stock that has run out
+
+/// Items a shop sells, how many are left and what they cost
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StockEntry {
+    pub item: String,
+    pub quantity: u32,
+    pub price: u32, // In coins, for one item
+}
+
+/// A shop loaded from a text file, with the stock it has left
+///
+/// The file format follows the dialogue files:
+///
+/// ```text
+/// # Comments start with '#'
+/// name = Sheep Shop
+/// farewell = Baa! Come again.    <- said when the player leaves, optional
+/// stock = wool 10 for 3          <- item, quantity and price in coins
+/// buys = wool for 2              <- what the shop pays for an item
+/// ```
+///
+/// Items sold to the shop join its stock if it sells them too, so the
+/// stock changes as the player trades.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Shop {
+    pub name: String,
+    pub farewell: Option<String>,
+    pub stock: Vec<StockEntry>,
+    pub buys: Vec<(String, u32)>, // Items the shop buys, with the coins it pays for one
+}
+
+/// Why a purchase or a sale didn't happen
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TradeError {
+    SoldOut,
+    NotEnoughCoins,
+    InventoryFull,
+    NotWanted,
+    NothingToSell,
+}
+
+impl fmt::Display for TradeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TradeError::SoldOut => write!(f, "That is sold out."),
+            TradeError::NotEnoughCoins => write!(f, "You can't afford that."),
+            TradeError::InventoryFull => write!(f, "You can't carry any more."),
+            TradeError::NotWanted => write!(f, "The shop doesn't buy that."),
+            TradeError::NothingToSell => write!(f, "You have none to sell."),
+        }
+    }
+}
+
+/// Whether the player is looking at what the shop sells or at what it buys
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TradeMode {
+    Buying,
+    Selling,
+}
+
+/// One line of the trade menu the player can pick
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TradeEntry {
+    Buy(usize), // Index into the shop's stock
+    Sell(String), // A carried item the shop buys
+    SwitchMode,
+    Leave,
+}
+
+/// A visit to a shop in progress
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Trade {
+    pub shop: String, // Name of the shop's file
+    pub mode: TradeMode,
+    pub selected: usize, // Index into the entries of the menu
+}
+
+impl Shop {
+    /// Load every `*.shop` file in a directory, keyed by file name
+    pub fn load_all(shops_dir: &Path) -> Result<HashMap<String, Shop>, MapError> {
+        let io_error = |error| MapError::Io {
+            path: shops_dir.to_path_buf(),
+            error,
+        };
+
+        let mut paths = Vec::new();
+        for entry in fs::read_dir(shops_dir).map_err(io_error)? {
+            let path = entry.map_err(io_error)?.path();
+            if path.extension().is_some_and(|extension| extension == "shop") {
+                paths.push(path);
+            }
+        }
+        paths.sort(); // Load in a stable order so errors are reproducible
+
+        let mut shops = HashMap::new();
+        for path in paths {
+            let name = path.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+            let source = fs::read_to_string(&path).map_err(|error| MapError::Io {
+                path: path.clone(),
+                error,
+            })?;
+            shops.insert(name, Shop::parse(&source, &path)?);
+        }
+        Ok(shops)
+    }
+
+    /// Parse shop file contents; `path` is only used in error messages
+    pub fn parse(source: &str, path: &Path) -> Result<Shop, MapError> {
+        let mut shop = Shop {
+            name: String::new(),
+            farewell: None,
+            stock: Vec::new(),
+            buys: Vec::new(),
+        };
+
+        for (index, line) in source.lines().enumerate() {
+            let syntax_error = |message: String| MapError::Syntax {
+                path: path.to_path_buf(),
+                line: index + 1,
+                message,
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue; // Skip blank lines and comments
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| syntax_error(format!("expected `key = value`, found `{}`", line)))?;
+            let (key, value) = (key.trim(), value.trim());
+            match key {
+                "name" => shop.name = value.to_string(),
+                "farewell" => shop.farewell = Some(value.to_string()),
+                "stock" => {
+                    let entry = match value.split_whitespace().collect::<Vec<_>>().as_slice() {
+                        [item, quantity, "for", price] => quantity.parse().ok().zip(price.parse().ok()).map(|(quantity, price)| StockEntry {
+                            item: item.to_string(),
+                            quantity,
+                            price,
+                        }),
+                        _ => None,
+                    };
+                    shop.stock
+                        .push(entry.ok_or_else(|| syntax_error(format!("expected `item quantity for price`, found `{}`", value)))?);
+                }
+                "buys" => {
+                    let offer = match value.split_whitespace().collect::<Vec<_>>().as_slice() {
+                        [item, "for", price] => price.parse().ok().map(|price| (item.to_string(), price)),
+                        _ => None,
+                    };
+                    shop.buys.push(offer.ok_or_else(|| syntax_error(format!("expected `item for price`, found `{}`", value)))?);
+                }
+                _ => return Err(syntax_error(format!("unknown shop property `{}`", key))),
+            }
+        }
+
+        if shop.name.is_empty() {
+            return Err(MapError::Syntax {
+                path: path.to_path_buf(),
+                line: 1,
+                message: "shop has no `name`".to_string(),
+            });
+        }
+        Ok(shop)
+    }
+
+    /// The coins the shop pays for one of an item, if it buys it
+    pub fn price_offered(&self, item: &str) -> Option<u32> {
+        self.buys.iter().find(|(bought, _)| bought == item).map(|(_, price)| *price)
+    }
+
+    /// Sell one of a stock entry to the player
+    pub fn buy(&mut self, index: usize, inventory: &mut Inventory, items: &HashMap<String, ItemDefinition>) -> Result<(), TradeError> {
+        let entry = &mut self.stock[index];
+        if entry.quantity == 0 {
+            return Err(TradeError::SoldOut);
+        }
+        if inventory.coins < entry.price {
+            return Err(TradeError::NotEnoughCoins);
+        }
+        if inventory.add(&entry.item, 1, items) > 0 {
+            return Err(TradeError::InventoryFull);
+        }
+        inventory.coins -= entry.price;
+        entry.quantity -= 1;
+        Ok(())
+    }
+
+    /// Buy one of an item from the player, returning the coins paid
+    pub fn sell(&mut self, item: &str, inventory: &mut Inventory) -> Result<u32, TradeError> {
+        let price = self.price_offered(item).ok_or(TradeError::NotWanted)?;
+        if !inventory.remove(item, 1) {
+            return Err(TradeError::NothingToSell);
+        }
+        inventory.coins += price;
+        if let Some(entry) = self.stock.iter_mut().find(|entry| entry.item == item) {
+            entry.quantity += 1;
+        }
+        Ok(price)
+    }
+}
+
+impl Trade {
+    /// Start a visit to a shop, looking at what it sells
+    pub fn open(shop: &str) -> Trade {
+        Trade {
+            shop: shop.to_string(),
+            mode: TradeMode::Buying,
+            selected: 0,
+        }
+    }
+
+    /// The entries of the menu: the stock, or the carried items the shop buys, then switching and leaving
+    pub fn entries(&self, shop: &Shop, inventory: &Inventory) -> Vec<TradeEntry> {
+        let mut entries: Vec<TradeEntry> = match self.mode {
+            TradeMode::Buying => (0..shop.stock.len()).map(TradeEntry::Buy).collect(),
+            TradeMode::Selling => shop
+                .buys
+                .iter()
+                .filter(|(item, _)| inventory.count(item) > 0)
+                .map(|(item, _)| TradeEntry::Sell(item.clone()))
+                .collect(),
+        };
+        entries.push(TradeEntry::SwitchMode);
+        entries.push(TradeEntry::Leave);
+        entries
+    }
+
+    /// The selected entry of the menu
+    pub fn selected_entry(&self, shop: &Shop, inventory: &Inventory) -> TradeEntry {
+        let mut entries = self.entries(shop, inventory);
+        let index = self.selected.min(entries.len() - 1); // Selling may have emptied the list
+        entries.swap_remove(index)
+    }
+
+    /// Move the selection up or down by `offset` entries, wrapping around
+    pub fn move_selection(&mut self, offset: isize, shop: &Shop, inventory: &Inventory) {
+        let entry_count = self.entries(shop, inventory).len() as isize;
+        self.selected = (self.selected as isize + offset).rem_euclid(entry_count) as usize;
+    }
+
+    /// Look at the other side of the trade
+    pub fn switch_mode(&mut self) {
+        self.mode = match self.mode {
+            TradeMode::Buying => TradeMode::Selling,
+            TradeMode::Selling => TradeMode::Buying,
+        };
+        self.selected = 0;
+    }
+
+    /// The menu as textbox lines `width` columns wide: a title with the
+    /// player's coins, then the entries with a marker on the selected one
+    pub fn menu_lines(&self, shop: &Shop, inventory: &Inventory, items: &HashMap<String, ItemDefinition>, width: usize) -> Vec<String> {
+        let mode = match self.mode {
+            TradeMode::Buying => "buying",
+            TradeMode::Selling => "selling",
+        };
+        let title = format!("{{{}}}{}{{/}}, {}", TITLE_COLOR, shop.name, mode);
+        let coins = format!("Coins: {}", inventory.coins);
+        let padding = width.saturating_sub(display_width(&title) + coins.len()).max(1);
+        let mut lines = vec![format!("{}{}{}", title, " ".repeat(padding), coins)];
+
+        let selected = self.selected.min(self.entries(shop, inventory).len() - 1);
+        for (index, entry) in self.entries(shop, inventory).iter().enumerate() {
+            let marker = if index == selected { '>' } else { ' ' };
+            let line = match entry {
+                TradeEntry::Buy(stock_index) => {
+                    let stock_entry = &shop.stock[*stock_index];
+                    let name = &items[&stock_entry.item].name;
+                    if stock_entry.quantity == 0 {
+                        let label = format!("{} sold out", name);
+                        format!("{} {{{}}}{}{{/}}", marker, SOLD_OUT_COLOR, label)
+                    } else {
+                        price_line(marker, &format!("{} x{}", name, stock_entry.quantity), stock_entry.price, width)
+                    }
+                }
+                TradeEntry::Sell(item) => {
+                    let label = format!("{} x{}", items[item].name, inventory.count(item));
+                    price_line(marker, &label, shop.price_offered(item).unwrap_or(0), width)
+                }
+                TradeEntry::SwitchMode => match self.mode {
+                    TradeMode::Buying => format!("{} Sell something", marker),
+                    TradeMode::Selling => format!("{} Buy something", marker),
+                },
+                TradeEntry::Leave => format!("{} Leave", marker),
+            };
+            lines.push(line);
+        }
+        lines
+    }
+}
+
+// Function to lay out a menu entry with its price right-aligned
+fn price_line(marker: char, label: &str, price: u32, width: usize) -> String {
+    let price = format!("{}c", price);
+    let padding = width.saturating_sub(label.chars().count() + price.len() + 2);
+    format!("{} {}{}{}", marker, label, " ".repeat(padding), price)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inventory::parse_items;
+
+    fn items() -> HashMap<String, ItemDefinition> {
+        parse_items("[wool]\nname = Wool\nstack = 20\n[shears]\nname = Shears\n", Path::new("items.txt")).unwrap()
+    }
+
+    fn shop() -> Shop {
+        Shop::parse(
+            "name = Sheep Shop\nstock = wool 2 for 3\nstock = shears 1 for 12\nbuys = wool for 2\n",
+            Path::new("sheep_shop.shop"),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn buying_costs_coins_and_runs_down_the_stock() {
+        let (items, mut shop) = (items(), shop());
+        let mut inventory = Inventory {
+            coins: 7,
+            ..Inventory::default()
+        };
+
+        assert_eq!(shop.buy(1, &mut inventory, &items), Err(TradeError::NotEnoughCoins));
+        assert_eq!(shop.buy(0, &mut inventory, &items), Ok(()));
+        assert_eq!(shop.buy(0, &mut inventory, &items), Ok(()));
+        assert_eq!(shop.buy(0, &mut inventory, &items), Err(TradeError::SoldOut));
+        assert_eq!((inventory.coins, inventory.count("wool")), (1, 2));
+    }
+
+    #[test]
+    fn selling_pays_coins_and_restocks_the_shop() {
+        let (items, mut shop) = (items(), shop());
+        let mut inventory = Inventory::default();
+        inventory.add("wool", 1, &items);
+        inventory.add("shears", 1, &items);
+
+        assert_eq!(shop.sell("shears", &mut inventory), Err(TradeError::NotWanted));
+        assert_eq!(shop.sell("wool", &mut inventory), Ok(2));
+        assert_eq!(shop.sell("wool", &mut inventory), Err(TradeError::NothingToSell));
+        assert_eq!((inventory.coins, shop.stock[0].quantity), (2, 3));
+    }
+
+    #[test]
+    fn the_menu_lists_prices_and_the_way_out() {
+        let (items, mut shop) = (items(), shop());
+        shop.stock[1].quantity = 0;
+        let inventory = Inventory {
+            coins: 5,
+            ..Inventory::default()
+        };
+        let mut trade = Trade::open("sheep_shop");
+
+        let lines = trade.menu_lines(&shop, &inventory, &items, 24);
+        assert_eq!(lines[0], "{yellow}Sheep Shop{/}, buying Coins: 5");
+        assert_eq!(lines[1], "> Wool x2             3c");
+        assert_eq!(lines[2], "  {gray}Shears sold out{/}");
+        assert_eq!(&lines[3..], ["  Sell something", "  Leave"]);
+
+        trade.move_selection(-1, &shop, &inventory);
+        assert_eq!(trade.selected_entry(&shop, &inventory), TradeEntry::Leave);
+        trade.switch_mode();
+        assert_eq!(trade.entries(&shop, &inventory), vec![TradeEntry::SwitchMode, TradeEntry::Leave]); // Nothing to sell
+    }
+}
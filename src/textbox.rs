@@ -0,0 +1,339 @@
+use crate::markup::{style_lines, StyledLine}; // Import textbox markup
+use crate::render::wrap_text; // Import text wrapping
+use crate::{TEXT_AREA_HEIGHT, TEXT_AREA_WIDTH}; // Import layout constants
+
+/// Lines shown at once, and revealed before waiting for the player to continue
+pub const PAGE_LINES: usize = (TEXT_AREA_HEIGHT - 1) as usize;
+/// Revealed lines kept to scroll back to; older ones are only kept in the message history
+pub const SCROLLBACK_LINES: usize = 100;
+const BLINK_PERIOD: f32 = 0.5; // Seconds the continue indicator stays on, then off
+
+/// What the textbox shows for one frame
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextboxView {
+    pub lines: Vec<StyledLine>, // At most `PAGE_LINES` lines
+    pub first_line: usize, // Index of the first line shown among all lines shown so far
+    pub total_lines: usize, // Lines shown so far, including those scrolled out of view
+    pub shows_continue_indicator: bool,
+    pub input_line: Option<String>, // The command being typed, shown on the bottom edge of the frame
+}
+
+/// A queue of messages revealed character by character, one page at a time
+///
+/// Pushed messages are wrapped and queued behind the text already shown,
+/// with their markup applied, so the reveal honours `{pause}` and `{speed}`
+/// tags. Revealing stops after every `PAGE_LINES` lines until the player
+/// continues, and the view follows the newest revealed line unless the
+/// player has scrolled back, at most `SCROLLBACK_LINES` lines.
+pub struct Textbox {
+    pub chars_per_second: f32, // Reveal rate of the typewriter
+    lines: Vec<StyledLine>, // Wrapped lines of the messages pushed, from the oldest kept for scrolling back
+    revealed_lines: usize, // Lines revealed completely
+    revealed_chars: usize, // Characters revealed of the line after those
+    reveal_budget: f32, // Progress towards revealing the next character, in characters
+    page_lines: usize, // Lines revealed since the player last continued
+    scroll_back: usize, // Lines the view is scrolled back from the newest one
+    blink_time: f32, // Seconds since the continue indicator last changed state
+}
+
+impl Textbox {
+    /// An empty textbox revealing `chars_per_second` characters per second
+    pub fn new(chars_per_second: f32) -> Textbox {
+        Textbox {
+            chars_per_second,
+            lines: Vec::new(),
+            revealed_lines: 0,
+            revealed_chars: 0,
+            reveal_budget: 0.0,
+            page_lines: 0,
+            scroll_back: 0,
+            blink_time: 0.0,
+        }
+    }
+
+    /// Queue a message behind the text already shown
+    pub fn push(&mut self, message: &str) {
+        let wrapped_text = wrap_text(message, TEXT_AREA_WIDTH as usize);
+        self.lines.extend(style_lines(wrapped_text.lines()));
+    }
+
+    /// Reveal the text due after `seconds` more seconds
+    pub fn update(&mut self, seconds: f32) {
+        self.blink_time = (self.blink_time + seconds) % (2.0 * BLINK_PERIOD);
+        if self.is_finished() || self.is_waiting() {
+            return;
+        }
+
+        let mut budget = self.reveal_budget + self.chars_per_second * seconds;
+        while !self.is_finished() && !self.is_waiting() {
+            let Some(styled_char) = self.lines[self.revealed_lines].get(self.revealed_chars) else {
+                self.reveal_line();
+                continue;
+            };
+            // A pause costs as many characters as could be revealed meanwhile
+            let cost = styled_char.pause * self.chars_per_second + 1.0 / styled_char.speed;
+            if budget < cost {
+                self.reveal_budget = budget;
+                return;
+            }
+            budget -= cost;
+            self.revealed_chars += 1;
+            self.scroll_back = 0; // Follow the text being revealed
+        }
+        self.reveal_budget = 0.0;
+    }
+
+    /// React to the continue key: turn the page, or finish revealing it.
+    /// Returns false if everything was already revealed.
+    pub fn advance(&mut self) -> bool {
+        if self.is_waiting() {
+            self.page_lines = 0;
+            self.blink_time = 0.0;
+            true
+        } else if !self.is_finished() {
+            while !self.is_finished() && !self.is_waiting() {
+                self.reveal_line();
+            }
+            self.reveal_budget = 0.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Whether a full page has been revealed and more text waits for the player
+    pub fn is_waiting(&self) -> bool {
+        self.page_lines >= PAGE_LINES && !self.is_finished()
+    }
+
+    /// Whether every queued message has been revealed
+    pub fn is_finished(&self) -> bool {
+        self.revealed_lines == self.lines.len()
+    }
+
+    /// Whether the blinking continue indicator is lit right now
+    pub fn is_indicator_lit(&self) -> bool {
+        self.blink_time < BLINK_PERIOD
+    }
+
+    /// The lines that fit in the textbox: the revealed text, followed by
+    /// `footer` once everything is revealed
+    pub fn view(&self, footer: &[String]) -> TextboxView {
+        let footer = footer_lines(footer);
+        let total_lines = self.revealed_lines + self.shown_after_revealed(footer.len());
+        let end = total_lines - self.scroll_back.min(self.max_scroll_back(footer.len()));
+        let first_line = end.saturating_sub(PAGE_LINES);
+
+        // Only the lines in view are copied, the revealed ones followed by the footer or the line being revealed
+        let lines = (first_line..end)
+            .map(|line| match line.checked_sub(self.revealed_lines) {
+                None => self.lines[line].clone(),
+                Some(footer_line) if self.is_finished() => footer[footer_line].clone(),
+                Some(_) => self.lines[self.revealed_lines][..self.revealed_chars].to_vec(),
+            })
+            .collect();
+        TextboxView {
+            lines,
+            first_line,
+            total_lines,
+            shows_continue_indicator: self.is_waiting() && self.is_indicator_lit(),
+            input_line: None,
+        }
+    }
+
+    /// The index of the `footer` entry shown on line `row` of the view, if
+    /// the footer is shown and that line belongs to one of its entries
+    pub fn footer_entry_at(&self, footer: &[String], row: usize) -> Option<usize> {
+        if !self.is_finished() {
+            return None;
+        }
+        let view = self.view(footer);
+        let line = view.first_line + row;
+        let mut entry_start = view.total_lines - footer_lines(footer).len();
+        for (index, entry) in footer.iter().enumerate() {
+            let entry_end = entry_start + wrap_text(entry, TEXT_AREA_WIDTH as usize).split('\n').count();
+            if (entry_start..entry_end).contains(&line) && row < view.lines.len() {
+                return Some(index);
+            }
+            entry_start = entry_end;
+        }
+        None
+    }
+
+    /// Scroll the view one line back, towards older text, below which
+    /// `footer` is shown
+    pub fn scroll_up(&mut self, footer: &[String]) {
+        self.scroll_back = (self.scroll_back + 1).min(self.max_scroll_back(footer_lines(footer).len()));
+    }
+
+    /// Scroll the view one line forward, towards the newest text
+    pub fn scroll_down(&mut self) {
+        self.scroll_back = self.scroll_back.saturating_sub(1);
+    }
+
+    // Function to count how far back the view can be scrolled
+    fn max_scroll_back(&self, footer_lines: usize) -> usize {
+        let shown_lines = self.revealed_lines + if self.is_finished() { footer_lines } else { 1 };
+        shown_lines.saturating_sub(PAGE_LINES)
+    }
+
+    // Function to count the lines shown below the revealed ones: the footer,
+    // the line being revealed, or none while waiting for the player
+    fn shown_after_revealed(&self, footer_lines: usize) -> usize {
+        if self.is_finished() {
+            footer_lines
+        } else if self.is_waiting() {
+            0
+        } else {
+            1
+        }
+    }
+
+    // Function to mark the next line as completely revealed, forgetting the
+    // oldest line once more than `SCROLLBACK_LINES` are kept
+    fn reveal_line(&mut self) {
+        self.revealed_lines += 1;
+        self.revealed_chars = 0;
+        self.page_lines += 1;
+        self.scroll_back = 0; // Follow the text being revealed
+        if self.revealed_lines > SCROLLBACK_LINES {
+            self.lines.remove(0);
+            self.revealed_lines -= 1;
+        }
+    }
+}
+
+// Function to wrap and style footer entries like messages
+fn footer_lines(footer: &[String]) -> Vec<StyledLine> {
+    let wrapped_entries: Vec<String> = footer
+        .iter()
+        .map(|entry| wrap_text(entry, TEXT_AREA_WIDTH as usize))
+        .collect();
+    style_lines(wrapped_entries.iter().flat_map(|entry| entry.split('\n')))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markup::line_text;
+
+    fn texts(view: TextboxView) -> Vec<String> {
+        view.lines.iter().map(|line| line_text(line)).collect()
+    }
+
+    fn textbox_with_lines(count: usize) -> Textbox {
+        let mut textbox = Textbox::new(10.0);
+        for number in 0..count {
+            textbox.push(&format!("line {}", number));
+        }
+        textbox
+    }
+
+    #[test]
+    fn characters_are_revealed_at_the_configured_rate() {
+        let mut textbox = Textbox::new(10.0);
+        textbox.push("Hello there");
+
+        textbox.update(0.5);
+        assert_eq!(texts(textbox.view(&[])), vec!["Hello"]);
+        textbox.update(0.6);
+        assert_eq!(texts(textbox.view(&[])), vec!["Hello there"]);
+        assert!(textbox.is_finished());
+    }
+
+    #[test]
+    fn pauses_and_speed_tags_pace_the_reveal() {
+        let mut textbox = Textbox::new(10.0);
+        textbox.push("ab{pause 500}cd {speed 2}efgh{/}");
+
+        textbox.update(0.2);
+        assert_eq!(texts(textbox.view(&[])), vec!["ab"]);
+        textbox.update(0.5);
+        assert_eq!(texts(textbox.view(&[])), vec!["ab"]); // Still pausing
+        textbox.update(0.1);
+        assert_eq!(texts(textbox.view(&[])), vec!["abc"]);
+        textbox.update(0.3);
+        assert_eq!(texts(textbox.view(&[])), vec!["abcd ef"]); // Twice as fast
+    }
+
+    #[test]
+    fn revealing_waits_at_page_boundaries() {
+        let mut textbox = textbox_with_lines(PAGE_LINES + 2);
+
+        textbox.update(100.0);
+        assert!(textbox.is_waiting());
+        assert_eq!(texts(textbox.view(&[])).last().unwrap(), &format!("line {}", PAGE_LINES - 1));
+
+        assert!(textbox.advance());
+        textbox.update(100.0);
+        assert!(textbox.is_finished());
+        assert_eq!(texts(textbox.view(&[])).last().unwrap(), &format!("line {}", PAGE_LINES + 1)); // Scrolled to the newest line
+        assert!(!textbox.advance());
+    }
+
+    #[test]
+    fn continuing_reveals_the_rest_of_the_page_at_once() {
+        let mut textbox = textbox_with_lines(3);
+        textbox.update(0.1);
+
+        assert!(textbox.advance());
+
+        assert!(textbox.is_finished());
+        assert_eq!(texts(textbox.view(&[])).len(), 3);
+    }
+
+    #[test]
+    fn footer_follows_the_text_once_it_is_revealed() {
+        let mut textbox = textbox_with_lines(2);
+        let footer = vec!["> yes".to_string()];
+
+        assert!(texts(textbox.view(&footer)).iter().all(|line| line.is_empty()));
+        textbox.advance();
+        assert_eq!(texts(textbox.view(&footer)), vec!["line 0", "line 1", "> yes"]);
+    }
+
+    #[test]
+    fn scrolling_back_stays_in_range() {
+        let mut textbox = textbox_with_lines(PAGE_LINES + 2);
+        textbox.advance();
+        textbox.advance();
+        textbox.advance();
+
+        for _ in 0..10 {
+            textbox.scroll_up(&[]);
+        }
+        assert_eq!(texts(textbox.view(&[]))[0], "line 0");
+        assert_eq!(textbox.view(&[]).first_line, 0);
+        textbox.scroll_down();
+        assert_eq!(texts(textbox.view(&[]))[0], "line 1");
+        assert_eq!((textbox.view(&[]).first_line, textbox.view(&[]).total_lines), (1, PAGE_LINES + 2));
+    }
+
+    #[test]
+    fn only_the_scrollback_is_kept() {
+        let mut textbox = textbox_with_lines(SCROLLBACK_LINES + 20);
+        while textbox.advance() {}
+
+        assert_eq!(textbox.lines.len(), SCROLLBACK_LINES);
+        assert_eq!(textbox.view(&[]).total_lines, SCROLLBACK_LINES);
+        for _ in 0..SCROLLBACK_LINES {
+            textbox.scroll_up(&[]);
+        }
+        assert_eq!(texts(textbox.view(&[]))[0], "line 20"); // The oldest line kept
+    }
+
+    #[test]
+    fn footer_entries_are_found_by_the_row_they_are_shown_on() {
+        let mut textbox = textbox_with_lines(PAGE_LINES - 1);
+        let footer = vec![
+            "> Yes".to_string(),
+            "  A choice long enough to wrap onto a second line".to_string(),
+            "  No".to_string(),
+        ];
+        assert_eq!(textbox.footer_entry_at(&footer, 0), None); // Not revealed yet
+
+        textbox.advance();
+        let rows: Vec<Option<usize>> = (0..PAGE_LINES + 1).map(|row| textbox.footer_entry_at(&footer, row)).collect();
+        assert_eq!(&rows[PAGE_LINES - 5..], [None, Some(0), Some(1), Some(1), Some(2), None]);
+    }
+}
@@ -0,0 +1,271 @@
+use std::collections::{HashMap, HashSet}; // Import HashMap and HashSet collections
+use std::fs; // Import file system access
+use std::path::Path; // Import path handling
+
+use crate::collision::{CollisionMap, DEFAULT_SOLID_GLYPHS}; // Import the collision model
+use crate::map::{GameMap, Layer, MapError}; // Import map file handling
+use crate::object::{load_objects, RoomObject}; // Import interactable objects
+
+/// One screen of the world, with its own grids and fog of war
+pub struct Room {
+    pub name: String, // Name shown to the player
+    pub description: Option<String>, // What the player sees when looking around
+    pub background: Layer,
+    pub landscape: Layer,
+    pub collision: CollisionMap,
+    pub west: Option<String>, // Room entered by walking past the left edge
+    pub east: Option<String>, // Room entered by walking past the right edge
+    pub north: Option<String>, // Room entered by walking past the top edge
+    pub south: Option<String>, // Room entered by walking past the bottom edge
+    pub revealed_positions: HashSet<(usize, usize)>,
+    pub dialogue: Option<(String, (i32, i32))>, // Dialogue held by talking near a landscape cell
+    pub objects: Vec<RoomObject>,
+}
+
+/// A compass direction, naming an edge of the landscape
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    North,
+    South,
+    East,
+    West,
+}
+
+/// The graph of rooms loaded from the rooms directory
+///
+/// Every `*.map` file in the directory is a room named after its file stem.
+/// Besides the `background` and `landscape` layers, a room map may set the
+/// `west`, `east`, `north` and `south` metadata keys to the names of its
+/// neighbouring rooms. The `name` key gives the name shown to the player
+/// and `description` what the player sees when looking around.
+///
+/// Walls come from an optional `collision` layer of the same size as the
+/// landscape, where every non-space cell is solid. Without one, landscape
+/// cells holding any glyph listed in the `solid` metadata key (or
+/// [`DEFAULT_SOLID_GLYPHS`]) are solid.
+///
+/// `dialogue = sheep_shop at 17,10` lets the character talk when standing
+/// next to the given landscape cell (column, row). Objects the character
+/// can look at and use are described by `object X` keys and marked in an
+/// optional `objects` layer, see [`load_objects`].
+pub struct World {
+    rooms: HashMap<String, Room>,
+    current_room: String,
+}
+
+impl Direction {
+    /// The lowercase name of the direction
+    pub fn name(self) -> &'static str {
+        match self {
+            Direction::North => "north",
+            Direction::South => "south",
+            Direction::East => "east",
+            Direction::West => "west",
+        }
+    }
+
+    /// The movement keys held down to walk in this direction
+    pub fn offset(self) -> (f32, f32) {
+        match self {
+            Direction::North => (0.0, -1.0),
+            Direction::South => (0.0, 1.0),
+            Direction::East => (1.0, 0.0),
+            Direction::West => (-1.0, 0.0),
+        }
+    }
+}
+
+impl Room {
+    /// The object the player calls `noun`, if the room has one
+    pub fn object_called(&self, noun: &str) -> Option<&RoomObject> {
+        self.objects.iter().find(|object| object.is_called(noun))
+    }
+
+    /// The room entered by walking past the edge in `direction`
+    pub fn exit(&self, direction: Direction) -> Option<&String> {
+        match direction {
+            Direction::North => self.north.as_ref(),
+            Direction::South => self.south.as_ref(),
+            Direction::East => self.east.as_ref(),
+            Direction::West => self.west.as_ref(),
+        }
+    }
+
+    /// Reveal every landscape cell within `radius` cells of `center`
+    pub fn reveal_around(&mut self, center: (i32, i32), radius: i32) {
+        let (center_x, center_y) = center;
+        for row in (center_y - radius).max(0)..=(center_y + radius).min(self.landscape.height as i32 - 1) {
+            for col in (center_x - radius).max(0)..=(center_x + radius).min(self.landscape.width as i32 - 1) {
+                self.revealed_positions.insert((row as usize, col as usize));
+            }
+        }
+    }
+}
+
+impl World {
+    /// Load every room in `rooms_dir` and start in `start_room`
+    pub fn load(rooms_dir: &Path, start_room: &str) -> Result<World, MapError> {
+        let io_error = |error| MapError::Io {
+            path: rooms_dir.to_path_buf(),
+            error,
+        };
+
+        let mut paths = Vec::new();
+        for entry in fs::read_dir(rooms_dir).map_err(io_error)? {
+            let path = entry.map_err(io_error)?.path();
+            if path.extension().is_some_and(|extension| extension == "map") {
+                paths.push(path);
+            }
+        }
+        paths.sort(); // Load in a stable order so errors are reproducible
+
+        let mut rooms = HashMap::new();
+        for path in paths {
+            let room_name = path.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+            let mut game_map = GameMap::load(&path)?;
+            let landscape = game_map.take_layer("landscape")?;
+            let collision = match game_map.layers.remove("collision") {
+                Some(collision_layer) => CollisionMap::from_collision_layer(&collision_layer),
+                None => {
+                    let solid_glyphs = game_map.metadata.get("solid").map_or(DEFAULT_SOLID_GLYPHS, String::as_str);
+                    CollisionMap::from_landscape(&landscape, solid_glyphs)
+                }
+            };
+            let dialogue = match game_map.metadata.get("dialogue") {
+                Some(value) => Some(parse_dialogue_spot(value).ok_or_else(|| MapError::InvalidMetadata {
+                    path: path.clone(),
+                    key: "dialogue".to_string(),
+                    value: value.clone(),
+                })?),
+                None => None,
+            };
+            let objects = load_objects(&mut game_map)?;
+            let room = Room {
+                name: game_map.metadata.get("name").cloned().unwrap_or_else(|| room_name.clone()),
+                description: game_map.metadata.get("description").cloned(),
+                background: game_map.take_layer("background")?,
+                landscape,
+                collision,
+                west: game_map.metadata.get("west").cloned(),
+                east: game_map.metadata.get("east").cloned(),
+                north: game_map.metadata.get("north").cloned(),
+                south: game_map.metadata.get("south").cloned(),
+                revealed_positions: HashSet::new(),
+                dialogue,
+                objects,
+            };
+            rooms.insert(room_name, room);
+        }
+
+        // Check that every edge leads to a room that exists
+        for (room_name, room) in &rooms {
+            for neighbour in [&room.west, &room.east, &room.north, &room.south].into_iter().flatten() {
+                if !rooms.contains_key(neighbour) {
+                    return Err(MapError::UnknownRoom {
+                        path: rooms_dir.join(format!("{}.map", room_name)),
+                        room: neighbour.clone(),
+                    });
+                }
+            }
+        }
+        if !rooms.contains_key(start_room) {
+            return Err(MapError::UnknownRoom {
+                path: rooms_dir.to_path_buf(),
+                room: start_room.to_string(),
+            });
+        }
+
+        Ok(World {
+            rooms,
+            current_room: start_room.to_string(),
+        })
+    }
+
+    /// Every room with its name
+    pub fn rooms(&self) -> impl Iterator<Item = (&String, &Room)> {
+        self.rooms.iter()
+    }
+
+    /// The name of the room the character is in
+    pub fn current_room_name(&self) -> &str {
+        &self.current_room
+    }
+
+    /// The room the character is in
+    pub fn current_room(&self) -> &Room {
+        &self.rooms[&self.current_room]
+    }
+
+    /// The room the character is in, for updating its fog of war
+    pub fn current_room_mut(&mut self) -> &mut Room {
+        self.rooms
+            .get_mut(&self.current_room)
+            .expect("current room is always loaded")
+    }
+
+    /// The room called `room_name`, for restoring a saved game
+    pub fn room_mut(&mut self, room_name: &str) -> Option<&mut Room> {
+        self.rooms.get_mut(room_name)
+    }
+
+    /// Put the character in another room without crossing an edge, as when loading a saved game
+    pub fn enter(&mut self, room_name: &str) {
+        assert!(self.rooms.contains_key(room_name), "room `{}` is not loaded", room_name);
+        self.current_room = room_name.to_string();
+    }
+
+    /// Move into the neighbouring room if the character has walked past an
+    /// edge of the landscape, and return the character's new position.
+    /// Without a neighbour the character stops at the edge.
+    pub fn cross_edge(&mut self, character_position: (i32, i32), character_size: (i32, i32)) -> (i32, i32) {
+        let (mut character_x, mut character_y) = character_position;
+        let (character_width, character_height) = character_size;
+        let room = self.current_room();
+        let (landscape_width, landscape_height) = (room.landscape.width as i32, room.landscape.height as i32);
+
+        // Pick the edge that was crossed, if any, and where to enter the next room
+        let crossing = if character_x < 0 {
+            character_x = 0;
+            room.west.clone().map(|west| (west, Edge::Right))
+        } else if character_x + character_width > landscape_width {
+            character_x = landscape_width - character_width;
+            room.east.clone().map(|east| (east, Edge::Left))
+        } else if character_y < 0 {
+            character_y = 0;
+            room.north.clone().map(|north| (north, Edge::Bottom))
+        } else if character_y + character_height > landscape_height {
+            character_y = landscape_height - character_height;
+            room.south.clone().map(|south| (south, Edge::Top))
+        } else {
+            None
+        };
+
+        if let Some((next_room, entry_edge)) = crossing {
+            self.current_room = next_room;
+            let landscape = &self.current_room().landscape;
+            match entry_edge {
+                Edge::Left => character_x = 0,
+                Edge::Right => character_x = landscape.width as i32 - character_width,
+                Edge::Top => character_y = 0,
+                Edge::Bottom => character_y = landscape.height as i32 - character_height,
+            }
+        }
+
+        (character_x, character_y)
+    }
+}
+
+// Function to parse a `name at col,row` dialogue spot
+fn parse_dialogue_spot(value: &str) -> Option<(String, (i32, i32))> {
+    let (name, cell) = value.split_once(" at ")?;
+    let (col, row) = cell.split_once(',')?;
+    Some((name.trim().to_string(), (col.trim().parse().ok()?, row.trim().parse().ok()?)))
+}
+
+// The edge of the landscape a character enters a room from
+enum Edge {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
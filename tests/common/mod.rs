@@ -0,0 +1,93 @@
+// Golden-image comparison for off-screen renders.
+//
+// Each test renders part of the screen and compares it with
+// `tests/golden/<name>.bmp`. A missing golden image fails the test;
+// `UPDATE_GOLDEN=1 cargo test` writes all of them from the current render,
+// to be reviewed and committed. When a comparison fails,
+// the actual render is saved under cargo's test temp directory next to the
+// path printed in the failure message.
+
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
+
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::surface::Surface;
+
+const GOLDEN_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden");
+const CHANNEL_TOLERANCE: u8 = 16; // Largest color channel difference still treated as equal
+const PIXEL_TOLERANCE: f64 = 0.002; // Fraction of pixels allowed to differ
+
+static SDL_LOCK: Mutex<()> = Mutex::new(());
+
+/// Serialize SDL use, since tests run on several threads
+pub fn sdl_lock() -> MutexGuard<'static, ()> {
+    SDL_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Compare a render with its golden image, or write the golden image with `UPDATE_GOLDEN` set
+pub fn assert_matches_golden(name: &str, actual: &Surface) {
+    let golden_path = PathBuf::from(GOLDEN_DIR).join(format!("{}.bmp", name));
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::create_dir_all(GOLDEN_DIR).unwrap();
+        actual.save_bmp(&golden_path).unwrap();
+        eprintln!("Wrote golden image {}, review and commit it", golden_path.display());
+        return;
+    }
+
+    assert!(
+        golden_path.exists(),
+        "golden image {} is missing, run with UPDATE_GOLDEN=1 to write it",
+        golden_path.display()
+    );
+    let expected = Surface::load_bmp(&golden_path).unwrap();
+    if let Err(message) = compare_surfaces(&expected, actual) {
+        let actual_path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(format!("{}.actual.bmp", name));
+        actual.save_bmp(&actual_path).unwrap();
+        panic!(
+            "{} differs from {}: {} (actual render saved to {})",
+            name,
+            golden_path.display(),
+            message,
+            actual_path.display()
+        );
+    }
+}
+
+// Function to compare two surfaces pixel by pixel within the tolerances
+fn compare_surfaces(expected: &Surface, actual: &Surface) -> Result<(), String> {
+    if expected.size() != actual.size() {
+        return Err(format!("size is {:?}, expected {:?}", actual.size(), expected.size()));
+    }
+
+    // Compare both in the same 32-bit format, whatever the files were saved as
+    let expected = expected.convert_format(PixelFormatEnum::RGB888)?;
+    let actual = actual.convert_format(PixelFormatEnum::RGB888)?;
+    let (width, height) = expected.size();
+    let (expected_pitch, actual_pitch) = (expected.pitch() as usize, actual.pitch() as usize);
+
+    let differing_pixels = expected.with_lock(|expected_pixels| {
+        actual.with_lock(|actual_pixels| {
+            let mut count = 0;
+            for row in 0..height as usize {
+                for col in 0..width as usize {
+                    // The fourth byte of each RGB888 pixel is unused padding
+                    let expected_pixel = &expected_pixels[row * expected_pitch + col * 4..][..3];
+                    let actual_pixel = &actual_pixels[row * actual_pitch + col * 4..][..3];
+                    let differs = expected_pixel
+                        .iter()
+                        .zip(actual_pixel)
+                        .any(|(a, b)| a.abs_diff(*b) > CHANNEL_TOLERANCE);
+                    count += differs as usize;
+                }
+            }
+            count
+        })
+    });
+
+    let allowed = (width as f64 * height as f64 * PIXEL_TOLERANCE) as usize;
+    if differing_pixels > allowed {
+        return Err(format!("{} pixels differ, at most {} allowed", differing_pixels, allowed));
+    }
+    Ok(())
+}
@@ -0,0 +1,121 @@
+mod common;
+
+use std::path::Path;
+
+use sdl2::ttf::Sdl2TtfContext;
+
+use the_fabricof::game::GameState;
+use the_fabricof::headless::{self, InputScript};
+use the_fabricof::render::{render_background_and_landscape, render_character, render_grid, render_textbox, TEXTBOX_FRAME};
+use the_fabricof::{FONT_PATH, FONT_SIZE, MAPS_DIR, START_ROOM};
+
+use common::{assert_matches_golden, sdl_lock};
+
+fn new_game() -> GameState {
+    GameState::load(Path::new(MAPS_DIR), START_ROOM).unwrap()
+}
+
+fn ttf_context() -> Sdl2TtfContext {
+    headless::init().unwrap()
+}
+
+#[test]
+fn background() {
+    let _sdl = sdl_lock();
+    let ttf_context = ttf_context();
+    let font = ttf_context.load_font(FONT_PATH, FONT_SIZE).unwrap();
+    let game_state = new_game();
+    let background = &game_state.world.current_room().background;
+
+    let frame = headless::render_offscreen(&font, 1, |glyph_atlas, canvas| {
+        render_grid(&background.rows, glyph_atlas, canvas, 1.0, 1.0, background.color);
+    })
+    .unwrap();
+
+    assert_matches_golden("background", &frame);
+}
+
+#[test]
+fn landscape_with_fog() {
+    let _sdl = sdl_lock();
+    let ttf_context = ttf_context();
+    let font = ttf_context.load_font(FONT_PATH, FONT_SIZE).unwrap();
+    let game_state = new_game();
+    let room = game_state.world.current_room();
+
+    let frame = headless::render_offscreen(&font, 1, |glyph_atlas, canvas| {
+        render_background_and_landscape(
+            &room.background,
+            &room.landscape,
+            glyph_atlas,
+            canvas,
+            1.0,
+            1.0,
+            &room.revealed_positions,
+        );
+    })
+    .unwrap();
+
+    assert_matches_golden("landscape_with_fog", &frame);
+}
+
+#[test]
+fn character() {
+    let _sdl = sdl_lock();
+    let ttf_context = ttf_context();
+    let font = ttf_context.load_font(FONT_PATH, FONT_SIZE).unwrap();
+    let game_state = new_game();
+    let character = &game_state.character_picture;
+
+    let frame = headless::render_offscreen(&font, 1, |glyph_atlas, canvas| {
+        render_character(&character.rows, character.color, glyph_atlas, canvas, 1.0, 1.0, (7, 8));
+    })
+    .unwrap();
+
+    assert_matches_golden("character", &frame);
+}
+
+#[test]
+fn textbox() {
+    let _sdl = sdl_lock();
+    let ttf_context = ttf_context();
+    let font = ttf_context.load_font(FONT_PATH, FONT_SIZE).unwrap();
+    let mut game_state = new_game();
+    game_state.textbox.advance(); // Reveal the first page
+
+    let frame = headless::render_offscreen(&font, 1, |glyph_atlas, canvas| {
+        render_textbox(&game_state.textbox_view(), TEXTBOX_FRAME, glyph_atlas, canvas, 1.0, 1.0);
+    })
+    .unwrap();
+
+    assert_matches_golden("textbox", &frame);
+}
+
+#[test]
+fn full_frame_before_and_after_a_walk() {
+    let _sdl = sdl_lock();
+    let ttf_context = ttf_context();
+    let font = ttf_context.load_font(FONT_PATH, FONT_SIZE).unwrap();
+    let mut game_state = new_game();
+
+    let start = headless::render_to_surface(&game_state, &font, 1).unwrap();
+    assert_matches_golden("frame_start", &start);
+
+    InputScript::load(Path::new("tests/scripts/walk_to_shop.txt"))
+        .unwrap()
+        .run(&mut game_state);
+    let after_walk = headless::render_to_surface(&game_state, &font, 1).unwrap();
+    assert_matches_golden("frame_after_walk", &after_walk);
+}
+
+#[test]
+fn full_frame_scaled() {
+    let _sdl = sdl_lock();
+    let ttf_context = ttf_context();
+    let font = ttf_context.load_font(FONT_PATH, FONT_SIZE).unwrap();
+    let game_state = new_game();
+
+    let frame = headless::render_to_surface(&game_state, &font, 3).unwrap();
+
+    assert_matches_golden("frame_start_x3", &frame);
+}
@@ -0,0 +1,70 @@
+use std::path::Path;
+
+use the_fabricof::game::{Command, GameState};
+use the_fabricof::headless::{self, InputScript, ScriptStep};
+use the_fabricof::{BASE_HEIGHT, BASE_WIDTH, FONT_PATH, FONT_SIZE, MAPS_DIR, START_ROOM};
+
+fn new_game() -> GameState {
+    GameState::load(Path::new(MAPS_DIR), START_ROOM).unwrap()
+}
+
+#[test]
+fn script_steps_are_parsed() {
+    let script = InputScript::parse("# comment\n\nhold up+left 3\nwait 2\npress scroll_down\ntype go  east\nclick 20 4\n").unwrap();
+
+    assert_eq!(
+        script.steps,
+        vec![
+            ScriptStep::Hold { direction: (-1.0, -1.0), ticks: 3 },
+            ScriptStep::Hold { direction: (0.0, 0.0), ticks: 2 },
+            ScriptStep::Press(Command::ScrollDown),
+            ScriptStep::Type("go east".to_string()),
+            ScriptStep::Press(Command::Click(20, 4)),
+        ]
+    );
+}
+
+#[test]
+fn script_errors_name_the_line() {
+    let error = InputScript::parse("wait 1\nhold sideways 3\n").unwrap_err();
+
+    assert_eq!(error.line, 2);
+    assert_eq!(error.to_string(), "line 2: unknown direction `sideways`");
+}
+
+#[test]
+fn replaying_a_script_is_deterministic() {
+    let script = InputScript::load(Path::new("tests/scripts/walk_to_shop.txt")).unwrap();
+    let mut first = new_game();
+    let mut second = new_game();
+
+    assert_eq!(script.run(&mut first), 27);
+    script.run(&mut second);
+
+    assert_eq!(first.character_cell(), second.character_cell());
+    assert_eq!(first.textbox_view(), second.textbox_view());
+    assert!(first.textbox.is_waiting()); // The first page was revealed at once
+}
+
+#[test]
+fn walls_stop_a_scripted_walk() {
+    let mut game_state = new_game();
+
+    InputScript::parse("hold right 600").unwrap().run(&mut game_state);
+
+    assert_eq!(game_state.character_cell(), (10, 8)); // Stopped by the Sheep Shop wall, under the sprite's blank right column
+}
+
+#[test]
+fn final_frame_renders_without_a_display() {
+    let mut game_state = new_game();
+    InputScript::parse("hold left 10").unwrap().run(&mut game_state);
+
+    let ttf_context = headless::init().unwrap();
+    let font = ttf_context.load_font(FONT_PATH, FONT_SIZE).unwrap();
+    let frame = headless::render_to_surface(&game_state, &font, 1).unwrap();
+
+    assert_eq!((frame.width(), frame.height()), (BASE_WIDTH, BASE_HEIGHT));
+    let has_drawn_pixels = frame.with_lock(|pixels| pixels.iter().any(|&byte| byte != 0));
+    assert!(has_drawn_pixels);
+}
@@ -0,0 +1,135 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use the_fabricof::game::GameState;
+use the_fabricof::headless::InputScript;
+use the_fabricof::markup::line_text;
+use the_fabricof::movement::CharacterPosition;
+use the_fabricof::save::{autosave_path, read_save, slot_path};
+use the_fabricof::{MAPS_DIR, START_ROOM};
+
+// Start a game that keeps its saves in a fresh directory of its own
+fn new_game(test_name: &str) -> GameState {
+    let saves_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("saves").join(test_name);
+    let _ = fs::remove_dir_all(&saves_dir);
+    let mut game_state = GameState::load(Path::new(MAPS_DIR), START_ROOM).unwrap();
+    game_state.saves_dir = saves_dir;
+    game_state
+}
+
+fn run(game_state: &mut GameState, script: &str) {
+    InputScript::parse(script).unwrap().run(game_state);
+    while game_state.textbox.advance() {} // Show every message at once
+}
+
+fn last_line(game_state: &GameState) -> String {
+    line_text(game_state.textbox_view().lines.last().unwrap())
+}
+
+#[test]
+fn loading_brings_back_the_saved_game() {
+    let mut game_state = new_game("round_trip");
+    run(&mut game_state, "hold left 10\nhold down 3\ntype save 2\n");
+    assert_eq!(last_line(&game_state), "Game saved in slot 2.");
+    let saved_cell = game_state.character_cell();
+    let saved_revealed = game_state.world.current_room().revealed_positions.clone();
+    let saved_messages = game_state.message_log.entries.len();
+
+    run(&mut game_state, "press close_input\nhold up 20\nhold left 30\n");
+    game_state.flags.insert("met_keeper".to_string());
+    game_state.inventory.coins = 40;
+    assert_ne!(game_state.character_cell(), saved_cell);
+
+    run(&mut game_state, "type load 2\n");
+    assert_eq!(last_line(&game_state), "Game loaded from slot 2.");
+    assert_eq!(game_state.character_cell(), saved_cell);
+    assert_eq!(game_state.world.current_room().revealed_positions, saved_revealed);
+    assert!(game_state.flags.is_empty());
+    assert_eq!(game_state.inventory.coins, 0);
+    assert_eq!(game_state.message_log.entries.len(), saved_messages); // Saved before the message about saving, loaded after
+}
+
+#[test]
+fn the_load_menu_lists_the_slots() {
+    let mut game_state = new_game("load_menu");
+    run(&mut game_state, "type save 1\npress close_input\n");
+    fs::write(slot_path(&game_state.saves_dir, 3), "version = 1\nroom = start\nposition = nowhere\nend = 3\n").unwrap();
+
+    run(&mut game_state, "press toggle_load_menu\nhold right 10\n");
+    let menu: Vec<String> = game_state.slot_menu_view().unwrap().lines.iter().map(|line| line_text(line)).collect();
+    assert_eq!(menu[2], "> Slot 1  Start, 00:00");
+    assert_eq!(&menu[3..], ["  Slot 2  Empty", "  Slot 3  Damaged"]);
+    assert_eq!(game_state.ticks, 2); // Paused while the menu is open
+
+    run(&mut game_state, "press select_next\npress confirm\n");
+    assert!(game_state.slot_menu_view().is_none());
+    assert_eq!(last_line(&game_state), "Slot 2 is empty.");
+
+    run(&mut game_state, "press toggle_load_menu\npress select_previous\npress confirm\n");
+    let message = &game_state.message_log.entries.last().unwrap().message;
+    assert!(message.starts_with("{red}Loading failed:{/}") && message.ends_with("slot3.sav:3: invalid position `nowhere`"));
+}
+
+#[test]
+fn autosaves_rotate_on_entering_rooms_and_over_time() {
+    let mut game_state = new_game("autosave_rotation");
+    game_state.is_autosaving = true;
+    game_state.character_position = CharacterPosition::new(34, 8); // Past the tree
+    run(&mut game_state, "type go east\nwait 120\n");
+    let saves_dir = game_state.saves_dir.clone();
+    assert_eq!(read_save(&autosave_path(&saves_dir, 0)).unwrap().unwrap().room, "forest");
+
+    run(&mut game_state, "type go west\nwait 120\n");
+    assert_eq!(read_save(&autosave_path(&saves_dir, 0)).unwrap().unwrap().room, "start");
+    assert_eq!(read_save(&autosave_path(&saves_dir, 1)).unwrap().unwrap().room, "forest");
+
+    run(&mut game_state, "wait 7200\n"); // Two more autosaves, a minute apart
+    let newest = read_save(&autosave_path(&saves_dir, 0)).unwrap().unwrap();
+    assert_eq!(newest.ticks, game_state.last_autosave);
+    assert_eq!(read_save(&autosave_path(&saves_dir, 2)).unwrap().unwrap().room, "start"); // The forest one was dropped
+    assert!(!autosave_path(&saves_dir, 3).exists());
+    assert!(fs::read_dir(&saves_dir).unwrap().all(|entry| !entry.unwrap().path().to_string_lossy().ends_with(".tmp")));
+}
+
+#[test]
+fn a_cut_short_autosave_is_set_aside_on_startup() {
+    let mut game_state = new_game("autosave_recovery");
+    game_state.is_autosaving = true;
+    game_state.character_position = CharacterPosition::new(34, 8); // Past the tree
+    run(&mut game_state, "type go east\nwait 120\ntype go west\nwait 120\n");
+    let saves_dir = game_state.saves_dir.clone();
+    let newest_path = autosave_path(&saves_dir, 0);
+    let newest = fs::read_to_string(&newest_path).unwrap();
+    fs::write(&newest_path, &newest[..newest.len() / 2]).unwrap(); // As if the power went out while writing
+
+    let mut restarted = GameState::load(Path::new(MAPS_DIR), START_ROOM).unwrap();
+    restarted.saves_dir = saves_dir.clone();
+    restarted.resume_autosave();
+
+    assert_eq!(restarted.world.current_room_name(), "forest");
+    assert!(newest_path.with_extension("sav.damaged").exists() && !newest_path.exists());
+    let messages: Vec<&str> = restarted.message_log.entries.iter().map(|entry| entry.message.as_str()).collect();
+    assert!(messages[messages.len() - 2].contains("save was cut short"));
+    assert!(messages[messages.len() - 1].starts_with("Welcome back! The game carries on from the autosave in Forest, "));
+}
+
+#[test]
+fn autosaves_from_a_newer_game_are_left_in_place() {
+    let mut game_state = new_game("autosave_newer");
+    game_state.is_autosaving = true;
+    game_state.character_position = CharacterPosition::new(34, 8); // Past the tree
+    run(&mut game_state, "type go east\nwait 120\n");
+    let saves_dir = game_state.saves_dir.clone();
+    let newest_path = autosave_path(&saves_dir, 0);
+    fs::write(&newest_path, "version = 99\nroom = forest\n").unwrap();
+
+    let mut restarted = GameState::load(Path::new(MAPS_DIR), START_ROOM).unwrap();
+    restarted.saves_dir = saves_dir.clone();
+    restarted.resume_autosave();
+
+    assert!(newest_path.exists() && !newest_path.with_extension("sav.damaged").exists());
+    let messages: Vec<&str> = restarted.message_log.entries.iter().map(|entry| entry.message.as_str()).collect();
+    let last = messages.last().unwrap();
+    assert!(last.contains("could not be loaded") && last.ends_with("save format version 99 is newer than this game"));
+    assert_eq!(restarted.world.current_room_name(), "start"); // No other autosave to carry on from
+}